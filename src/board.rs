@@ -1,27 +1,101 @@
 use crate::{WIDTH, HEIGHT};
-use crate::shape::{Point, ShapeMat, Shape};
-use std::marker::Copy;
+use crate::shape::{Point, ShapeRows, Shape};
 use rand::Rng;
 
 
+/// What fills a cell: part of a locked shape, or garbage sent up from below.
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct Board(pub [[Option<Shape>; WIDTH]; HEIGHT]);
+pub enum Cell {
+    Shape(Shape),
+    Garbage
+}
+
+impl From<Shape> for Cell {
+    fn from(s: Shape) -> Cell {
+        Cell::Shape(s)
+    }
+}
+
+/// Rows of cells, bottom row first.  Each row is a bitmask with bit x set
+/// when column x is filled, which is all collision and line checks look
+/// at; what filled each cell is kept alongside for drawing, a row at a time
+/// in one flat list.
+/// Boards can be up to 64 cells wide.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Board {
+    width: usize,
+    rows: Vec<u64>,
+    colours: Vec<Option<Cell>>
+}
+
+impl Default for Board {
+    fn default() -> Board {
+        return Board::new();
+    }
+}
 
 impl Board {
 
     pub fn new() -> Board {
-        return Board([[None; WIDTH]; HEIGHT]);
+        return Board::with_size(WIDTH, HEIGHT);
+    }
+
+    pub fn with_size(width: usize, height: usize) -> Board {
+        assert!(width <= 64, "boards are at most 64 wide");
+        return Board {
+            width: width,
+            rows: vec![0; height],
+            colours: vec![None; width * height]
+        };
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn height(&self) -> usize {
+        return self.rows.len();
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Cell> {
+        return self.colours[y * self.width + x];
     }
 
-    pub fn trash(&mut self, amt: u8) {
+    pub fn set(&mut self, x: usize, y: usize, cell: Option<Cell>) {
+        match cell {
+            Some(_) => self.rows[y] |= 1 << x,
+            None => self.rows[y] &= !(1 << x)
+        }
+        self.colours[y * self.width + x] = cell;
+    }
+
+    /// The filled cells of row `y` as a bitmask, bit x for column x.
+    pub fn row(&self, y: usize) -> u64 {
+        return self.rows[y];
+    }
+
+    pub fn fill_row(&mut self, y: usize, cell: Option<Cell>) {
+        for x in 0..self.width {
+            self.set(x, y, cell);
+        }
+    }
+
+    fn full_row(&self) -> u64 {
+        return match self.width {
+            64 => u64::MAX,
+            w => (1 << w) - 1
+        };
+    }
+
+    pub fn trash<R: Rng + ?Sized>(&mut self, amt: u8, rng: &mut R) {
         for _ in 0..amt {
             let mut done = false;
             while !done {
-                let x = rand::thread_rng().gen_range(0, WIDTH);
-                let y = rand::thread_rng().gen_range(0, HEIGHT);
-                if self.0[y][x] == None {
+                let x = rng.gen_range(0, self.width());
+                let y = rng.gen_range(0, self.height());
+                if self.get(x, y) == None {
                     done = true;
-                    self.0[y][x] = Some(Shape::random());
+                    self.set(x, y, Some(Shape::random(rng).into()));
                 }
             }
         }
@@ -29,72 +103,144 @@ impl Board {
 
     pub fn report(&self) -> String {
         let mut board_report = String::new();
-        board_report.push_str(&format!("[  ]----{:02}----\r\n", HEIGHT));
-        for y in (0..HEIGHT).rev() {
-            let row = self.0[y];
+        board_report.push_str(&format!("[  ]----{:02}----\r\n", self.height()));
+        for y in (0..self.height()).rev() {
             board_report.push_str(&format!("{:02} ", y));
-            for cell in row.iter() {
-                board_report.push_str(match cell {
-                    Some(_) => "x",
-                    None => " ",
+            for x in 0..self.width {
+                board_report.push_str(match self.rows[y] & 1 << x {
+                    0 => " ",
+                    _ => "x",
                 })
             }
             board_report.push_str("\r\n");
         }
-        board_report.push_str("-------------\r\n");
-        board_report.push_str("  |0123456789\r\n");
+        board_report.push_str(&format!("---{}\r\n", "-".repeat(self.width())));
+        board_report.push_str("  |");
+        for x in 0..self.width() {
+            board_report.push_str(&format!("{}", x % 10));
+        }
+        board_report.push_str("\r\n");
         board_report
     }
 
-    pub fn occupy(&mut self, m: &ShapeMat, p: &Point) {
-        for y in 0..4 {
+    /// True if a shape at `p` would hang off the board or overlap something
+    /// already on it.
+    pub fn collides(&self, shape: &ShapeRows, p: &Point) -> bool {
+        for (y, bits) in shape.iter().enumerate() {
+            if *bits == 0 {
+                continue;
+            }
+            let shifted = (*bits as u128) << p.x;
+            if shifted >> self.width != 0 || y + p.y >= self.height() {
+                return true;
+            }
+            if self.rows[y + p.y] & shifted as u64 != 0 {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// True if the cell is filled or lies outside the board.
+    pub fn blocked(&self, x: isize, y: isize) -> bool {
+        if x < 0 || y < 0 || x as usize >= self.width() || y as usize >= self.height() {
+            return true;
+        }
+        return self.rows[y as usize] & 1 << x != 0;
+    }
+
+    pub fn occupy(&mut self, shape: &ShapeRows, p: &Point, cell: Cell) {
+        for (y, bits) in shape.iter().enumerate() {
             for x in 0..4 {
-                // 0 is the bottom of the board
-                // so invert the y coordinate for this
-                // shape
-                if m[3-y][x] != None {
-                    self.0[y+p.y][x+p.x] = m[3-y][x];
+                if bits & 1 << x != 0 {
+                    self.set(x + p.x, y + p.y, Some(cell));
                 }
             }
         }
     }
 
-    pub fn vacate(&mut self, m: &ShapeMat, p: &Point) {
-        for y in 0..4 {
+    pub fn vacate(&mut self, shape: &ShapeRows, p: &Point) {
+        for (y, bits) in shape.iter().enumerate() {
             for x in 0..4 {
-                if m[3-y][x] != None && self.0[y+p.y][x+p.x] != None  {
-                    self.0[y+p.y][x+p.x] = None;
+                if bits & 1 << x != 0 {
+                    self.set(x + p.x, y + p.y, None);
                 }
             }
         }
     }
 
-    pub fn reset(&mut self) {
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                self.0[y][x] = None;
+    /// Pushes the stack up a row for each hole and fills the new bottom
+    /// rows with garbage, leaving the hole's column empty.  The last hole
+    /// ends up on the bottom row.  Returns false if anything was pushed out
+    /// of the top of the board.
+    pub fn push_garbage(&mut self, holes: &[usize]) -> bool {
+        let mut fits = true;
+        let full = self.full_row();
+        for hole in holes.iter() {
+            if self.rows.pop() != Some(0) {
+                fits = false;
             }
+            self.colours.truncate(self.colours.len() - self.width);
+            let mut colours = vec![Some(Cell::Garbage); self.width];
+            colours[*hole] = None;
+            self.rows.insert(0, full & !(1 << hole));
+            self.colours.splice(0..0, colours);
         }
+        return fits;
     }
 
-    pub fn setup(&mut self, config: Vec<Vec<Option<Shape>>>, position: Point, overwrite: bool) {
-        let mut x;
+    /// Removes every full row, drops the rows above into the gaps, and
+    /// returns how many went.
+    pub fn clear_full_rows(&mut self) -> u8 {
+        let full = self.full_row();
+        let mut cleared = 0;
         let mut y = 0;
+        while y < self.rows.len() {
+            if self.rows[y] == full {
+                self.rows.remove(y);
+                self.colours.drain(y * self.width..(y + 1) * self.width);
+                cleared += 1;
+            } else {
+                y += 1;
+            }
+        }
+        for _ in 0..cleared {
+            self.rows.push(0);
+            self.colours.extend(vec![None; self.width]);
+        }
+        return cleared;
+    }
+
+    /// How many rows up the highest filled cell is, 0 for an empty board.
+    pub fn stack_height(&self) -> usize {
+        return self.rows.iter().rposition(|row| *row != 0).map_or(0, |y| y + 1);
+    }
+
+    /// How many rows hold any garbage.
+    pub fn garbage_rows(&self) -> usize {
+        return self.colours.chunks(self.width.max(1)).filter(|row| row.contains(&Some(Cell::Garbage))).count();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.rows.iter().all(|row| *row == 0);
+    }
+
+    pub fn reset(&mut self) {
+        for y in 0..self.height() {
+            self.fill_row(y, None);
+        }
+    }
+
+    pub fn setup(&mut self, config: Vec<Vec<Option<Shape>>>, position: Point, overwrite: bool) {
         let config_height = config.len();
-        for row in config.iter() {
-            x = 0;
-            for cell in row.iter() {
-                if overwrite {
-                    self.0[config_height - y + position.y - 1][x + position.x] = *cell;
-                } else {
-                    if None == self.0[config_height - y + position.y - 1][x + position.x] {
-                        self.0[config_height - y + position.y - 1][x + position.x] = *cell;
-                    }                    
+        for (y, row) in config.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let (bx, by) = (x + position.x, config_height - y + position.y - 1);
+                if overwrite || None == self.get(bx, by) {
+                    self.set(bx, by, cell.map(Cell::Shape));
                 }
-                x += 1;
             }
-            y += 1;
         }
     }
 
-}
\ No newline at end of file
+}
@@ -0,0 +1,56 @@
+use crate::randomizer::RandomizerKind;
+use crate::rotation::RotationKind;
+use crate::scoring::ScoringKind;
+use crate::gravity::GravityCurve;
+use crate::garbage::{HolePattern, AttackTable};
+use crate::mode::GameMode;
+use crate::{WIDTH, HEIGHT};
+
+/// The rules a game is played under.  Everything here is fixed when the
+/// game is created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameConfig {
+    pub mode: GameMode,
+    pub randomizer: RandomizerKind,
+    pub preview: usize, // how many upcoming shapes are shown, 1 to 7
+    pub rotation: RotationKind,
+    pub tick_rate: u32, // how many TickGame inputs make a second of game time
+    pub lock_delay: u32, // ms a grounded shape waits before it locks
+    pub lock_resets: u32, // moves or turns that may restart the lock delay
+    pub soft_drop: u32, // how many times faster than gravity a soft drop falls
+    pub scoring: ScoringKind,
+    pub gravity: GravityCurve,
+    pub start_level: u8,
+    pub lines_per_level: u32, // lines to clear for each level up, 0 never levels up
+    pub width: usize, // up to 64
+    pub height: usize, // visible rows
+    pub vanish_zone: usize, // rows above the visible field where shapes spawn, raised to RotationSystem::vanish_zone if smaller
+    pub partial_lock_out: bool, // lose when a shape locks even partly in the vanish zone
+    pub garbage_holes: HolePattern,
+    pub attack: AttackTable, // garbage sent to opponents for each kind of clear
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig {
+            mode: GameMode::Endless,
+            randomizer: RandomizerKind::Bag,
+            preview: 5,
+            rotation: RotationKind::Srs,
+            tick_rate: 60,
+            lock_delay: 500,
+            lock_resets: 15,
+            soft_drop: 10,
+            scoring: ScoringKind::Guideline,
+            gravity: GravityCurve::Guideline,
+            start_level: 1,
+            lines_per_level: 10,
+            width: WIDTH,
+            height: HEIGHT - 5,
+            vanish_zone: 5,
+            partial_lock_out: false,
+            garbage_holes: HolePattern::Clean,
+            attack: AttackTable::default(),
+        }
+    }
+}
@@ -2,41 +2,70 @@ use crate::board::Board;
 use crate::shape::Shape;
 use crate::shape::Orientation;
 use crate::shape::Point;
+use crate::scoring::ScoreReason;
+use crate::spin::Spin;
+use crate::versus::MatchResult;
+use crate::mode::GameResult;
+
+/// Why a game was lost.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TopOut {
+    BlockOut, // the next shape had no room to spawn
+    LockOut, // a shape locked wholly above the visible field
+    PartialLockOut, // a shape locked partly above the visible field
+    GarbageOut, // garbage pushed the stack out of the top of the board
+}
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum Output {
-    GameOver,
+    GameOver(Option<TopOut>), // None when the game was ended or completed rather than lost
     GameStarted,
     GameRunning,
     BoardUpdate(Board),
     HeldShape(Shape),
     NextShape(Shape),
+    NextShapes(Vec<Shape>), // the whole preview queue, next shape first
     RotatedShape(Orientation),
     MovedShape,
     ShapePosition(Shape, Option<Orientation>, Orientation, Option<Point>, Point),
+    GhostPosition(Shape, Orientation, Point), // where the active shape would land
     ShapeLocked(Shape, Board),
     LineCompleted(u8, Board), // how many lines?
+    TSpin(Spin, u8), // the spin and how many lines it cleared
+    PerfectClear(u8), // a clear of this many lines left the board empty
+    GarbageReceived(u32), // rows of garbage pushed in under the stack
+    PendingGarbage(u32), // rows of garbage waiting to rise, for the meter
+    GarbageSent(u32), // rows of attack left over after cancelling pending garbage
+    Combo(u32, u32), // locks in a row that cleared lines, back to back difficult clears
+    Scored(ScoreReason, u32), // points and what they were for, before the new ScoreUpdate
     ScoreUpdate(u32),
+    LevelUp(u8), // the new level
+    Recovered(TopOut), // a top out cleared the board and play went on, see GameMode::Zen
+    Finished(GameResult), // sent once when a game ends, just after GameOver
+    MatchOver(MatchResult), // sent to every player once all but one have gone out
 }
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum Input {
     StartGame,
     EndGame,
-    TickGame,
+    TickGame, // one frame of game time, see GameConfig::tick_rate
+    SetLevel(u8),
     Left,
     Right,
-    Down,
+    Down, // soft drop, held until DownReleased
+    DownReleased,
     Drop,
     Hold,
     Cw,
     Ccw,
+    Rotate180,
+    Garbage(u32), // queue this many rows of garbage to rise on the next lock that clears nothing
 }
 use rand::Rng;
 
 impl Input {
-    pub fn rand_control() -> Input {
-        let mut rng = rand::thread_rng();
+    pub fn rand_control<R: Rng + ?Sized>(rng: &mut R) -> Input {
         match rng.gen_range(2, 6) {
             2 => Input::Left,
             3 => Input::Right,
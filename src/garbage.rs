@@ -0,0 +1,91 @@
+use crate::spin::Spin;
+use rand::Rng;
+use rand::RngCore;
+
+/// Which column is left open in each row of garbage.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HolePattern {
+    Clean, // one random column for the whole batch
+    Cheese, // a fresh random column every row
+    Column(usize), // always this column
+}
+
+impl HolePattern {
+    pub fn holes(&self, lines: u32, width: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        match self {
+            HolePattern::Clean => {
+                let hole = rng.gen_range(0, width);
+                vec![hole; lines as usize]
+            },
+            HolePattern::Cheese => (0..lines).map(|_| rng.gen_range(0, width)).collect(),
+            HolePattern::Column(x) => vec![(*x).min(width - 1); lines as usize],
+        }
+    }
+}
+
+/// What a lock cleared, as far as garbage is concerned.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Clear {
+    pub lines: u8,
+    pub spin: Option<Spin>,
+    pub combo: u32, // locks in a row that cleared lines, this one included
+    pub back_to_back: u32, // difficult clears in a row, this one included
+    pub perfect: bool,
+}
+
+/// Lines of garbage sent for each kind of clear.  The default is the
+/// guideline table: 0/1/2/4 for one to four lines, 2/4/6 for a T-spin
+/// single/double/triple, a line more for back to back, the combo table on
+/// top and 10 for a perfect clear.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttackTable {
+    pub lines: Vec<u32>, // one, two, three, four lines
+    pub t_spin: Vec<u32>, // T-spin single, double, triple
+    pub mini: Vec<u32>, // T-spin mini single, double
+    pub back_to_back: u32, // extra for a difficult clear following another
+    pub combo: Vec<u32>, // extra by how many clears in a row came before this one
+    pub perfect_clear: u32, // extra for emptying the board
+}
+
+impl Default for AttackTable {
+    fn default() -> AttackTable {
+        AttackTable {
+            lines: vec![0, 1, 2, 4],
+            t_spin: vec![2, 4, 6],
+            mini: vec![0, 1],
+            back_to_back: 1,
+            combo: vec![0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5],
+            perfect_clear: 10,
+        }
+    }
+}
+
+// the entry at i, or the last entry past the end of the table
+fn entry(table: &[u32], i: usize) -> u32 {
+    return table.get(i).or_else(|| table.last()).cloned().unwrap_or(0);
+}
+
+impl AttackTable {
+    /// Lines of garbage a clear sends.
+    pub fn attack(&self, c: &Clear) -> u32 {
+        if c.lines == 0 {
+            return 0;
+        }
+        let i = c.lines as usize - 1;
+        let mut lines = match c.spin {
+            Some(Spin::Full) => entry(&self.t_spin, i),
+            Some(Spin::Mini) => entry(&self.mini, i),
+            None => entry(&self.lines, i),
+        };
+        if c.back_to_back > 1 {
+            lines += self.back_to_back;
+        }
+        if c.combo > 0 {
+            lines += entry(&self.combo, c.combo as usize - 1);
+        }
+        if c.perfect {
+            lines += self.perfect_clear;
+        }
+        return lines;
+    }
+}
@@ -0,0 +1,50 @@
+/// Gravity is measured in 1/65536ths of a row per frame, so slow levels can
+/// fall a fraction of a row each frame and still keep close to their speed.
+/// `G` is a row every frame.
+pub const G: u32 = 65536;
+
+/// 20G: a shape hits the floor on the frame it arrives.
+pub const MAX_GRAVITY: u32 = 20 * G;
+
+// NTSC frames per row for NES levels 0 to 28; level 29 and up is 1.
+const NES_FRAMES: [u32; 29] = [
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6,
+    5, 5, 5, 4, 4, 4, 3, 3, 3, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2,
+];
+
+/// How fast shapes fall at each level.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GravityCurve {
+    Guideline, // (0.8 - (level - 1) * 0.007) ^ (level - 1) seconds a row, 20G from level 20
+    Nes, // the NES frame counts, scaled to the tick rate
+    Table(Vec<f64>), // frames a row takes at level 0, 1, 2 ..; under 1 falls rows a frame, the last entry holds for higher levels
+}
+
+impl GravityCurve {
+    /// Gravity at `level` in 1/65536ths of a row per frame (see `G`), for a
+    /// game running `tick_rate` frames a second.
+    pub fn gravity(&self, level: u8, tick_rate: u32) -> u32 {
+        let tick_rate = tick_rate.max(1) as f64;
+        let g = match self {
+            GravityCurve::Guideline => {
+                if level >= 20 {
+                    return MAX_GRAVITY;
+                }
+                let l = level.max(1) as f64 - 1.0;
+                let seconds = (0.8 - l * 0.007).powf(l);
+                G as f64 / (seconds * tick_rate)
+            },
+            GravityCurve::Nes => {
+                let frames = NES_FRAMES.get(level as usize).cloned().unwrap_or(1);
+                G as f64 * 60.0 / (frames as f64 * tick_rate)
+            },
+            GravityCurve::Table(table) => {
+                let frames = table.get(level as usize).or_else(|| table.last()).cloned().unwrap_or(1.0);
+                G as f64 / frames
+            }
+        };
+        // rounding up keeps a row from ever taking a frame longer than it should
+        return (g.ceil() as u32).clamp(1, MAX_GRAVITY);
+    }
+}
@@ -1,8 +1,34 @@
+// the codebase leans on explicit returns, `== None` checks and index loops
+// over the board; keep clippy focused on everything else.
+#![allow(
+    clippy::needless_return,
+    clippy::partialeq_to_none,
+    clippy::redundant_field_names,
+    clippy::needless_range_loop,
+)]
 pub mod shape;
 mod shape_state;
 pub mod event;
 pub mod board;
+pub mod config;
+pub mod randomizer;
+pub mod rotation;
+pub mod scoring;
+pub mod gravity;
+pub mod spin;
+pub mod garbage;
+pub mod versus;
+pub mod mode;
 use board::Board;
+use config::GameConfig;
+use randomizer::Randomizer;
+use rotation::RotationSystem;
+use scoring::{ScoringSystem, ScoreReason};
+use gravity::{G, MAX_GRAVITY};
+use spin::Spin;
+use garbage::{Clear, HolePattern};
+use versus::{Versus, Targeting, MatchResult};
+use mode::{GameMode, Outcome, GameResult};
 use shape_state::{ShapeState, Direction};
 use shape::{Shape, Point};
 use std::collections::VecDeque;
@@ -17,49 +43,130 @@ use uuid::Uuid;
 
 use std::sync::mpsc::{Sender, Receiver}; 
 
-use event::{Input, Output};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use event::{Input, Output, TopOut};
 const VERSION: f32 = 0.01;
+// the default board, vanish zone included; games take their size from GameConfig
 pub const WIDTH: usize  = 10;
 pub const HEIGHT: usize = 25;
 
-use log;
-
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum GameState {New, Playing, Over}
 
 pub struct Game {
     score: u32,
     shape_controller: ShapeState,
-    next_shape: Shape,
+    next_shapes: VecDeque<Shape>,
     hold_shape: Option<Shape>,
     state: GameState,
     pub board: Board,
-    double_down: bool,
-    down_ready: bool,
     tx: Sender<Output>,
-    input_buffer: VecDeque<Input>,
     hold_allowed: bool,
-    did_hold: bool
+    did_hold: bool,
+    seed: u64,
+    rng: StdRng,
+    garbage_rng: StdRng, // holes draw from their own rng so garbage never changes the shapes dealt
+    randomizer: Box<dyn Randomizer>,
+    rotation: Arc<dyn RotationSystem>,
+    scoring: Box<dyn ScoringSystem>,
+    config: GameConfig,
+    level: u8,
+    lines: u32,
+    frames: u64,
+    pieces: u32, // shapes locked
+    top_outs: u32, // top outs the mode played on through
+    gravity_acc: u32, // fractions of a row fallen since the last whole row, see gravity::G
+    lock_frames: u32,
+    lock_resets: u32,
+    lowest_y: usize,
+    lock_now: bool,
+    soft_drop: bool,
+    spin: Option<Spin>, // how the last shape to lock got there, for clear_lines
+    combo: u32, // locks in a row that cleared lines
+    back_to_back: u32, // tetrises and T-spin clears with no easier clear between
+    pending_garbage: VecDeque<u32>, // incoming batches of garbage, oldest first
+    dug_in: u32 // rows of garbage a dig has put on the board so far
 }
 
 impl Game {
-    pub fn new(tx : Sender<Output>) -> Game {
+    pub fn new(tx : Sender<Output>, seed: u64) -> Game {
+        Game::with_config(tx, seed, GameConfig::default())
+    }
+
+    /// Every random choice the game makes is drawn from an rng seeded with
+    /// `seed`, so the same seed and the same inputs produce the same outputs.
+    pub fn with_config(tx : Sender<Output>, seed: u64, config: GameConfig) -> Game {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut randomizer = config.randomizer.build();
+        let rotation = config.rotation.build();
+        let shape = randomizer.next(&mut rng);
+        let preview = config.preview.clamp(1, 7);
+        let next_shapes = (0..preview).map(|_| randomizer.next(&mut rng)).collect();
+        let shape_controller = ShapeState::new_from_shape(shape, rotation.clone(), config.width, config.height);
+        let lowest_y = shape_controller.position().y;
         Game {
             score: 0,
-            shape_controller: ShapeState::new(),
-            next_shape: Shape::random(),
+            shape_controller: shape_controller,
+            next_shapes: next_shapes,
             hold_shape: None,
             state: GameState::New,
-            board: Board::new(),
-            double_down: false,
-            down_ready: false,
+            board: Board::with_size(config.width, config.height + config.vanish_zone.max(rotation.vanish_zone())),
             tx: tx,
-            input_buffer: VecDeque::new(),
             hold_allowed: true,
-            did_hold: false
+            did_hold: false,
+            seed: seed,
+            rng: rng,
+            garbage_rng: StdRng::seed_from_u64(!seed),
+            randomizer: randomizer,
+            rotation: rotation,
+            scoring: config.scoring.build(),
+            level: config.start_level,
+            config: config,
+            lines: 0,
+            frames: 0,
+            pieces: 0,
+            top_outs: 0,
+            gravity_acc: 0,
+            lock_frames: 0,
+            lock_resets: 0,
+            lowest_y: lowest_y,
+            lock_now: false,
+            soft_drop: false,
+            spin: None,
+            combo: 0,
+            back_to_back: 0,
+            pending_garbage: VecDeque::new(),
+            dug_in: 0
       } 
     }
 
+    pub fn seed(&self) -> u64 {
+        return self.seed
+    }
+
+    pub fn set_level(&mut self, level: u8) {
+        self.level = level;
+    }
+
+    pub fn level(&self) -> u8 {
+        return self.level
+    }
+
+    // soft drop speeds gravity up by the configured factor
+    fn gravity(&self) -> u32 {
+        let g = self.config.gravity.gravity(self.level, self.config.tick_rate);
+        match self.soft_drop {
+            true => (g * self.config.soft_drop.max(1)).min(MAX_GRAVITY),
+            false => g
+        }
+    }
+
+    fn lock_delay_frames(&self) -> u32 {
+        return self.config.lock_delay * self.config.tick_rate / 1000;
+    }
+
     pub fn shape_controller(&mut self) -> &mut ShapeState {
         return &mut self.shape_controller
     }
@@ -69,221 +176,511 @@ impl Game {
     }
 
     pub fn report(&self) -> String {
-        let board = self.board;
+        let board = &self.board;
         let current_piece_status = format!("{:?}", self.get_shape_controller().position());
         let current_piece_orientation = format!("shape = {:?}, orientation = {:?}", self.shape_controller.shape(), self.shape_controller.orientation());
-        return String::from(format!("T E T R I X version {}\n{}\n{}\n{}\nscore: {}\nstate:{:?}\n", VERSION, current_piece_status, current_piece_orientation, board.report(), self.score, self.state))
+        return format!("T E T R I X version {}\n{}\n{}\n{}\nscore: {}\nstate:{:?}\n", VERSION, current_piece_status, current_piece_orientation, board.report(), self.score, self.state)
     }
 
-    fn check_collision(&self, s: &Shape, p: &Point) -> bool {
-        let m = s.to_mat(self.get_shape_controller().orientation());
-        let b = self.board;
-        for y in 0..4 {
-            for x in 0..4 {
-                let cell = m[3-y][x];
-                if cell != None && (x + p.x >= WIDTH) {
-                    return true;
-                }
-                if cell != None && b.0[y + p.y - 1][x + p.x] != None {
-                    return true;
-                }
-            }
-        }
-        return false;
+    // would the shape at `p` hit something one row further down?
+    fn check_collision(&self, p: &Point) -> bool {
+        let m = self.get_shape_controller().rows();
+        return p.y == 0 || self.board.collides(&m, &Point::new(p.x, p.y - 1));
     }
 
     pub fn shape_collides(&self) -> bool {
-        let s = &self.get_shape_controller().shape();
         let p = &self.get_shape_controller().position();
         if p.y == 0 {
             return true;
         }
-        return self.check_collision(s, p);
+        return self.check_collision(p);
     }
 
-    pub fn check_game_over(&self) -> bool {
-        let s = &self.get_shape_controller().shape();
-        let p = &self.get_shape_controller().position();
-        return p.y >= 20 && self.check_collision(s, p);
+    /// Whether locking the active shape where it is tops the game out: lock
+    /// out when it lies wholly in the vanish zone above the visible field,
+    /// and partial lock out, if enabled, when any of it does.
+    pub fn check_game_over(&self) -> Option<TopOut> {
+        let visible = self.config.height;
+        let p = self.shape_controller.position();
+        let rows: Vec<usize> = self.shape_controller.rows().iter().enumerate().filter(|(_, bits)| **bits != 0).map(|(y, _)| p.y + y).collect();
+        if rows.iter().all(|y| *y >= visible) {
+            return Some(TopOut::LockOut);
+        }
+        if self.config.partial_lock_out && rows.iter().any(|y| *y >= visible) {
+            return Some(TopOut::PartialLockOut);
+        }
+        return None;
+    }
+
+    // the mode decides whether a top out ends the game; if not, the board
+    // is cleared and play goes on with the next shape
+    fn top_out(&mut self, reason: TopOut) {
+        if self.config.mode.tops_out() {
+            self.finish(Outcome::ToppedOut(reason));
+            return;
+        }
+        self.top_outs += 1;
+        self.board.reset();
+        self.pending_garbage.clear();
+        self.spin = None;
+        self.tx.send(Output::Recovered(reason)).unwrap();
+    }
+
+    // every way a game ends comes through here
+    fn finish(&mut self, outcome: Outcome) {
+        self.state = GameState::Over;
+        let reason = match outcome {
+            Outcome::ToppedOut(reason) => Some(reason),
+            _ => None
+        };
+        self.tx.send(Output::GameOver(reason)).unwrap();
+        self.tx.send(Output::Finished(GameResult {
+            outcome: outcome,
+            time: self.time(),
+            frames: self.frames,
+            pieces: self.pieces,
+            top_outs: self.top_outs,
+            lines: self.lines,
+            score: self.score,
+            level: self.level
+        })).unwrap();
     }
 
-    pub fn rotate(&mut self, direction: Direction) {
+    // whether the mode's goal has been reached
+    fn completed(&self) -> bool {
+        return match self.config.mode {
+            GameMode::Endless | GameMode::Zen => false,
+            GameMode::Sprint(lines) | GameMode::Marathon(lines) => self.lines >= lines,
+            GameMode::Ultra(time) => self.time() >= time,
+            GameMode::Dig(lines, _) => self.dug_in >= lines && self.board.garbage_rows() == 0,
+        };
+    }
+
+    pub fn rotate(&mut self, direction: Direction) -> bool {
         let c = &mut self.shape_controller;
-        c.rotate(direction, &self.board);
-        self.tx.send(Output::RotatedShape(c.orientation())).unwrap();
+        let rotated = c.rotate(direction, &self.board);
+        if rotated {
+            self.tx.send(Output::RotatedShape(c.orientation())).unwrap();
+        }
+        return rotated;
+    }
+
+    fn spawn(&mut self, shape: Shape) {
+        self.shape_controller = ShapeState::new_from_shape(shape, self.rotation.clone(), self.config.width, self.config.height);
+        self.gravity_acc = 0;
+        self.lock_frames = 0;
+        self.lock_resets = 0;
+        self.lowest_y = self.shape_controller.position().y;
+        // no room for the new shape: block out
+        if self.shape_controller.shape_collide(&self.board) {
+            self.top_out(TopOut::BlockOut);
+        }
+    }
+
+    pub fn pending_garbage(&self) -> u32 {
+        return self.pending_garbage.iter().sum();
+    }
+
+    // incoming garbage waits until a shape locks without clearing anything
+    fn queue_garbage(&mut self, lines: u32) {
+        if lines == 0 {
+            return;
+        }
+        self.pending_garbage.push_back(lines);
+        self.tx.send(Output::PendingGarbage(self.pending_garbage())).unwrap();
+    }
+
+    // a clear's attack cancels pending garbage first, oldest first, and
+    // whatever is left over goes out to the other players
+    fn send_attack(&mut self, attack: u32) {
+        let pending = self.pending_garbage();
+        let mut attack = attack;
+        while attack > 0 {
+            match self.pending_garbage.front_mut() {
+                Some(batch) => {
+                    let cancelled = attack.min(*batch);
+                    *batch -= cancelled;
+                    attack -= cancelled;
+                    if *batch == 0 {
+                        self.pending_garbage.pop_front();
+                    }
+                },
+                None => break
+            }
+        }
+        if pending != self.pending_garbage() {
+            self.tx.send(Output::PendingGarbage(self.pending_garbage())).unwrap();
+        }
+        if attack > 0 {
+            self.tx.send(Output::GarbageSent(attack)).unwrap();
+        }
+    }
+
+    // pushes every pending batch in under the stack, each with its own holes
+    fn rise_garbage(&mut self) {
+        if self.pending_garbage.is_empty() {
+            return;
+        }
+        while let Some(lines) = self.pending_garbage.pop_front() {
+            let holes = self.config.garbage_holes.holes(lines, self.board.width(), &mut self.garbage_rng);
+            let fits = self.board.push_garbage(&holes);
+            self.tx.send(Output::GarbageReceived(lines)).unwrap();
+            if !fits {
+                self.pending_garbage.clear();
+                self.top_out(TopOut::GarbageOut);
+                break;
+            }
+        }
+        self.tx.send(Output::PendingGarbage(0)).unwrap();
+    }
+
+    // keeps a dig's garbage topped up until every row of it has gone in;
+    // the rows come in with random holes, like cheese
+    fn refill_dig(&mut self) {
+        if let GameMode::Dig(lines, rows) = self.config.mode {
+            let more = rows.saturating_sub(self.board.garbage_rows() as u32).min(lines.saturating_sub(self.dug_in));
+            if more == 0 {
+                return;
+            }
+            let holes = HolePattern::Cheese.holes(more, self.board.width(), &mut self.garbage_rng);
+            self.dug_in += more;
+            let fits = self.board.push_garbage(&holes);
+            self.tx.send(Output::GarbageReceived(more)).unwrap();
+            if !fits {
+                self.top_out(TopOut::GarbageOut);
+            }
+        }
+    }
+
+    fn fall(&mut self) -> bool {
+        if self.shape_collides() {
+            return false;
+        }
+        let moved = self.shape_controller.down();
+        let y = self.shape_controller.position().y;
+        // reaching a new lowest row starts the lock delay afresh and hands
+        // back every reset
+        if y < self.lowest_y {
+            self.lowest_y = y;
+            self.lock_frames = 0;
+            self.lock_resets = 0;
+        }
+        return moved;
+    }
+
+    /// Game time played so far in ms, counted in frames at the tick rate.
+    pub fn time(&self) -> u64 {
+        return self.frames * 1000 / self.config.tick_rate.max(1) as u64;
+    }
+
+    fn tick(&mut self) {
+        self.frames += 1;
+        if self.completed() {
+            self.finish(Outcome::Completed);
+            return;
+        }
+        let gravity = self.gravity();
+        self.gravity_acc += gravity;
+        // 20G goes all the way down, however far that is
+        let mut rows = match gravity >= MAX_GRAVITY {
+            true => self.board.height() as u32,
+            false => self.gravity_acc / G
+        };
+        self.gravity_acc %= G;
+        let mut fallen = 0;
+        while rows > 0 && self.fall() {
+            rows -= 1;
+            fallen += 1;
+        }
+        if fallen > 0 {
+            self.tx.send(Output::MovedShape).unwrap();
+            if self.soft_drop {
+                self.award(ScoreReason::SoftDrop(fallen));
+            }
+        }
+        if self.shape_collides() {
+            // nothing builds up while the shape sits on the stack
+            self.gravity_acc = 0;
+            self.lock_frames += 1;
+        } else if self.lock_frames > 0 {
+            // kicked back up off a row it has already landed on: the delay
+            // keeps running, and runs out as soon as it lands again
+            self.lock_frames += 1;
+        }
+    }
+
+    // moving or turning a grounded shape restarts the lock delay, but only
+    // `lock_resets` times before the timer is left to run out
+    fn reset_lock_delay(&mut self) {
+        if self.lock_frames > 0 && self.lock_resets < self.config.lock_resets {
+            self.lock_frames = 0;
+            self.lock_resets += 1;
+        }
     }
 
     fn action(&mut self, i: Input) {
-        self.double_down = false;
-        match i {
+        let moved = match i {
             Input::Left => match self.shape_controller.left(&self.board) {
-                true => self.tx.send(Output::MovedShape).unwrap(),
-                false => {}
+                true => {self.tx.send(Output::MovedShape).unwrap(); true},
+                false => false
             },
             Input::Right => match self.shape_controller.right(&self.board) {
-                true => self.tx.send(Output::MovedShape).unwrap(),
-                false => {}
+                true => {self.tx.send(Output::MovedShape).unwrap(); true},
+                false => false
+            },
+            Input::Drop => {
+                let start = self.shape_controller.position().y;
+                self.shape_controller.drop(&self.board);
+                let cells = start - self.shape_controller.position().y;
+                self.award(ScoreReason::HardDrop(cells as u32));
+                self.lock_now = true;
+                false
             },
-            Input::Drop => self.shape_controller.drop(&self.board),
-            Input::Down => {self.double_down = true},
+            Input::Down => {
+                // soft drop holds until the key comes up or the shape locks
+                self.soft_drop = true;
+                self.gravity_acc = 0;
+                if self.fall() {
+                    self.tx.send(Output::MovedShape).unwrap();
+                    self.award(ScoreReason::SoftDrop(1));
+                }
+                false
+            },
+            Input::DownReleased => {self.soft_drop = false; false},
             Input::Hold => {
                 /*
                     If there is a hold shape, pressing "hold" should switch the current shape
                     and the hold shape. The next shape stays the same.
 
                     If there is NOT a hold shape, pressing "hold" should make current shape the hold shape 
-                    and make the "next" shape the current shape. The "next next" is randomly generated.
+                    and make the "next" shape the current shape. The rest of the preview queue moves up
+                    and the randomizer deals a new shape onto the end.
                 */
                 if self.hold_allowed {
                     match self.hold_shape {
                         Some(shape) => {
                             self.hold_shape = Some(self.shape_controller.shape());
-                            self.shape_controller = ShapeState::new_from_shape(shape);
+                            self.spawn(shape);
                         },
                         None => {
                             self.hold_shape = Some(self.shape_controller.shape());
-                            self.shape_controller = ShapeState::new_from_shape(self.next_shape);
-                            self.next_shape = Shape::random();
-                            self.tx.send(Output::NextShape(self.next_shape)).unwrap();
+                            let shape = self.take_next_shape();
+                            self.spawn(shape);
                         }
                     }                
                     self.tx.send(Output::HeldShape(self.hold_shape.unwrap())).unwrap();
                     self.hold_allowed = false;
                     self.did_hold = true;
                 }
+                false
             },
-            Input::Cw => self.shape_controller.rotate(Direction::Cw, &self.board),
-            Input::Ccw => self.shape_controller.rotate(Direction::Ccw, &self.board),
-            Input::TickGame => {self.down_ready = true;},
-            _ => {}
+            Input::Cw => self.rotate(Direction::Cw),
+            Input::Ccw => self.rotate(Direction::Ccw),
+            Input::Rotate180 => self.rotate(Direction::Half),
+            Input::TickGame => {self.tick(); false},
+            _ => false
+        };
+        if moved {
+            self.reset_lock_delay();
         }
     }
 
     pub fn next(&mut self, i: Input) {
-        
-        self.input_buffer.push_front(i.clone());
-        if self.input_buffer.len() > 3 {
-            self.input_buffer.pop_back();
+        // garbage can come in before the player has started
+        if let Input::Garbage(lines) = i {
+            if self.state != GameState::Over {
+                self.queue_garbage(lines);
+            }
+            return;
         }
-
         match self.state { 
             GameState::Playing => {},
             _ => return,
         }
 
-        let from_point = self.shape_controller.position().clone();
-        let from_orientation = self.shape_controller.orientation().clone();
+        let from_point = *self.shape_controller.position();
+        let from_orientation = self.shape_controller.orientation();
 
-        self.board.vacate(
-            &self.shape_controller.shape().to_mat(self.shape_controller.orientation()),
-            self.shape_controller.position()
-        );
+        self.shape_controller.vacate(&mut self.board);
       
         self.action(i);
-
-        let count = match self.double_down {
-            true => 2,
-            false => 1
-        };
-
-        for _i in 0..count {
-            if self.shape_collides() {
-                let to_point = self.shape_controller.position().clone();
-                if self.check_game_over() {
-                    self.state = GameState::Over;
-                    self.tx.send(Output::GameOver).unwrap();
-                }
-                self.board.occupy(
-                    &self.shape_controller.shape().to_mat(self.shape_controller.orientation()),
-                    self.shape_controller.position()
-                );
-                // this would be the last gasp of the shape before it locks..
-
-                // if the last Input we got was a Tick, the lock it down - otherwise
-                // "continue" the loop and await more input????
-                // aftertouch code is here...
-                self.tx.send(Output::ShapePosition(self.shape_controller.shape(), Some(from_orientation), self.shape_controller.orientation(), Some(from_point), to_point)).unwrap();                
-                
-                if self.input_buffer[0] == Input::Drop || (self.down_ready && self.input_buffer.len() > 1 && self.input_buffer[0] == Input::TickGame && self.input_buffer[1] == Input::TickGame) {
-                    
-                    self.tx.send(Output::ShapeLocked(self.shape_controller.shape(), self.board)).unwrap();
-                    self.hold_allowed = true;
-                    
-                    self.shape_controller = ShapeState::new_from_shape(self.next_shape);                
-                    self.next_shape = Shape::random();                                
-                    self.tx.send(Output::NextShape(self.next_shape)).unwrap();
-
-                    let to_point = self.shape_controller.position().clone();
-                    // this is the new shape
-                    self.tx.send(Output::ShapePosition(self.shape_controller.shape(), None, self.shape_controller.orientation(), None, to_point)).unwrap();        
-                    self.clear_lines(); 
-                }
-            } else {
-                if self.down_ready {
-                    match self.shape_controller.down() {
-                        true => self.tx.send(Output::MovedShape).unwrap(),
-                        false => {}
+        if self.state == GameState::Over {
+            self.tx.send(Output::BoardUpdate(self.board.clone())).unwrap();
+            return;
+        }
+        // worked out while the shape is still off the board
+        let mut ghost = self.shape_controller.ghost(&self.board);
+
+        if self.shape_collides() {
+            let to_point = *self.shape_controller.position();
+            self.shape_controller.occupy(&mut self.board);
+            // this would be the last gasp of the shape before it locks..
+            self.tx.send(Output::ShapePosition(self.shape_controller.shape(), Some(from_orientation), self.shape_controller.orientation(), Some(from_point), to_point)).unwrap();                
+            
+            if self.lock_now || self.lock_frames >= self.lock_delay_frames() {
+                self.lock_now = false;
+                self.soft_drop = false;
+                self.tx.send(Output::ShapeLocked(self.shape_controller.shape(), self.board.clone())).unwrap();
+                self.hold_allowed = true;
+                self.pieces += 1;
+                self.spin = spin::t_spin(&self.shape_controller, &self.board);
+
+                match self.check_game_over() {
+                    Some(reason) => self.top_out(reason),
+                    None => {
+                        let cleared = self.clear_lines();
+                        if cleared != 0 {
+                            self.refill_dig();
+                        }
+                        if self.completed() {
+                            self.finish(Outcome::Completed);
+                        } else if cleared == 0 {
+                            self.rise_garbage();
+                        }
                     }
-                    self.down_ready = false;
                 }
-                let to_point = self.shape_controller.position().clone();
-                if self.did_hold {
+                if self.state == GameState::Playing {
+                    let shape = self.take_next_shape();
+                    self.spawn(shape);
+                    ghost = self.shape_controller.ghost(&self.board);
+
+                    let to_point = *self.shape_controller.position();
+                    // this is the new shape
                     self.tx.send(Output::ShapePosition(self.shape_controller.shape(), None, self.shape_controller.orientation(), None, to_point)).unwrap();
-                    self.did_hold = false;
-                } else {
-                    self.tx.send(Output::ShapePosition(self.shape_controller.shape(), Some(from_orientation), self.shape_controller.orientation(), Some(from_point), to_point)).unwrap();        
                 }
-                self.board.occupy(
-                    &self.shape_controller.shape().to_mat(self.shape_controller.orientation()),
-                    self.shape_controller.position()
-                );
             }
+        } else {
+            let to_point = *self.shape_controller.position();
+            if self.did_hold {
+                self.tx.send(Output::ShapePosition(self.shape_controller.shape(), None, self.shape_controller.orientation(), None, to_point)).unwrap();
+                self.did_hold = false;
+            } else {
+                self.tx.send(Output::ShapePosition(self.shape_controller.shape(), Some(from_orientation), self.shape_controller.orientation(), Some(from_point), to_point)).unwrap();        
+            }
+            self.shape_controller.occupy(&mut self.board);
         }
-        self.tx.send(Output::BoardUpdate(self.board)).unwrap();
+        if self.state == GameState::Playing {
+            self.send_ghost(ghost);
+        }
+        self.tx.send(Output::BoardUpdate(self.board.clone())).unwrap();
     }
 
     pub fn start(&mut self) {
         self.state = GameState::Playing;
         self.tx.send(Output::GameStarted).unwrap();
-        self.tx.send(Output::NextShape(self.next_shape)).unwrap();
+        self.refill_dig();
+        self.send_next_shapes();
+        self.send_ghost(self.shape_controller.ghost(&self.board));
+    }
+
+    // where the active shape would land, worked out while it is off the board
+    fn send_ghost(&self, ghost: Point) {
+        self.tx.send(Output::GhostPosition(self.shape_controller.shape(), self.shape_controller.orientation(), ghost)).unwrap();
+    }
+
+    // pulls the head of the preview queue and deals a new shape onto the tail
+    fn take_next_shape(&mut self) -> Shape {
+        let shape = self.next_shapes.pop_front().unwrap();
+        self.next_shapes.push_back(self.randomizer.next(&mut self.rng));
+        self.send_next_shapes();
+        return shape;
+    }
+
+    fn send_next_shapes(&self) {
+        self.tx.send(Output::NextShape(self.next_shapes[0])).unwrap();
+        self.tx.send(Output::NextShapes(self.next_shapes.iter().cloned().collect())).unwrap();
     }
 
     pub fn quit(&mut self) {
-        self.state = GameState::Over;
-        self.tx.send(Output::GameOver).unwrap();
+        self.finish(Outcome::Ended);
     }
 
-    pub fn clear_lines(&mut self) {
-        let mut clear_count : u8 = 0;
-        let mut y = 0;
-        
-        'outer: while y < HEIGHT {        
-            for x in 0..WIDTH {
-                if self.board.0[y][x] == None{
-                    y += 1;
-                    continue 'outer;
-                }
+    fn award(&mut self, reason: ScoreReason) -> u32 {
+        let points = self.scoring.points(&reason, self.level);
+        if points != 0 {
+            self.score += points;
+            self.tx.send(Output::Scored(reason, points)).unwrap();
+            self.tx.send(Output::ScoreUpdate(self.score)).unwrap();
+        }
+        return points;
+    }
+
+    pub fn combo(&self) -> u32 {
+        return self.combo;
+    }
+
+    pub fn back_to_back(&self) -> u32 {
+        return self.back_to_back;
+    }
+
+    // every `lines_per_level` lines takes the game up a level from where it started
+    fn advance_level(&mut self, lines: u32) {
+        self.lines += lines;
+        if self.config.lines_per_level == 0 {
+            return;
+        }
+        let level = (self.config.start_level as u32 + self.lines / self.config.lines_per_level).min(u8::MAX as u32) as u8;
+        if level > self.level {
+            self.level = level;
+            self.tx.send(Output::LevelUp(level)).unwrap();
+        }
+    }
+
+    pub fn clear_lines(&mut self) -> u8 {
+        let clear_count = self.board.clear_full_rows();
+        let spin = self.spin.take();
+        let streak = (self.combo, self.back_to_back);
+        // a lock that clears nothing ends the combo but leaves back to back alone
+        if clear_count == 0 {
+            self.combo = 0;
+        } else {
+            self.combo += 1;
+            if clear_count >= 4 || spin != None {
+                self.back_to_back += 1;
+            } else {
+                self.back_to_back = 0;
             }
-            'fall: for z in y..HEIGHT - 1 {
-                let mut empty_line = true;
-                for x in 0..WIDTH {
-                    if self.board.0[z+1][x] != None {
-                        empty_line = false;
-                    }
-                    self.board.0[z][x] = self.board.0[z+1][x];
-                }
-                if empty_line {
-                    break 'fall;
-                }
+        }
+
+        let reason = match spin {
+            Some(spin) => {
+                self.tx.send(Output::TSpin(spin, clear_count)).unwrap();
+                Some(ScoreReason::TSpin(spin, clear_count))
+            },
+            None if clear_count != 0 => Some(ScoreReason::Lines(clear_count)),
+            None => None
+        };
+        if let Some(reason) = reason {
+            let points = self.award(reason);
+            if clear_count != 0 && self.back_to_back > 1 {
+                self.award(ScoreReason::BackToBack(points));
             }
-            clear_count += 1;
         }
-        // TODO: more complex score calculation
-        self.score += clear_count as u32;
+        if self.combo > 1 {
+            self.award(ScoreReason::Combo(self.combo - 1));
+        }
+        if streak != (self.combo, self.back_to_back) {
+            self.tx.send(Output::Combo(self.combo, self.back_to_back)).unwrap();
+        }
+
         if clear_count != 0 {
-            self.tx.send(Output::ScoreUpdate(self.score)).unwrap();
-            self.tx.send(Output::LineCompleted(clear_count, self.board)).unwrap();
+            let perfect = self.board.is_empty();
+            if perfect {
+                self.tx.send(Output::PerfectClear(clear_count)).unwrap();
+                self.award(ScoreReason::PerfectClear(clear_count));
+            }
+            self.advance_level(clear_count as u32);
+            self.tx.send(Output::LineCompleted(clear_count, self.board.clone())).unwrap();
+            let attack = self.config.attack.attack(&Clear {
+                lines: clear_count,
+                spin: spin,
+                combo: self.combo,
+                back_to_back: self.back_to_back,
+                perfect: perfect
+            });
+            self.send_attack(attack);
         }
+        return clear_count;
     }
 
 }
@@ -295,21 +692,24 @@ use std::sync::mpsc::channel;
 pub struct GameHandle {
     join_handle: thread::JoinHandle<GameState>,
     output_receiver: Arc<Mutex<Receiver<Output>>>,
-    input_sender: Arc<Mutex<Sender<Input>>>
+    input_sender: Arc<Mutex<Sender<Input>>>,
+    tick_rate: u32
 }
 
 impl GameHandle {
+    #[allow(clippy::type_complexity)]
     pub fn tuple(&self) -> (&thread::JoinHandle<GameState>, Arc<Mutex<Receiver<Output>>>, Arc<Mutex<Sender<Input>>>) {
         (&self.join_handle, self.output_receiver.clone(), self.input_sender.clone())
     }
 }
 
-pub fn game() -> GameHandle {
+pub fn game(seed: u64, config: GameConfig) -> GameHandle {
     let (txo, rxo) = channel();
     let (txi, rxi) = channel();
+    let tick_rate = config.tick_rate;
 
     let h = thread::spawn(move|| {
-        let mut g = Game::new(txo);
+        let mut g = Game::with_config(txo, seed, config);
         while g.state != GameState::Over {
             let mut check_messages = true;
             while check_messages {
@@ -322,6 +722,9 @@ pub fn game() -> GameHandle {
                             Input::EndGame => {
                                 g.quit();
                             },
+                            Input::SetLevel(level) => {
+                                g.set_level(level);
+                            },
                             _ => {
                                 g.next(r);
                             }
@@ -335,23 +738,27 @@ pub fn game() -> GameHandle {
         }
         g.state
     });
-    return GameHandle{join_handle: h, output_receiver: Arc::new(Mutex::new(rxo)), input_sender: Arc::new(Mutex::new(txi))};
+    return GameHandle{join_handle: h, output_receiver: Arc::new(Mutex::new(rxo)), input_sender: Arc::new(Mutex::new(txi)), tick_rate: tick_rate};
 }
 
 pub struct GameWrapper {
     h: GameHandle,
-    ob: Arc<Mutex<VecDeque<Output>>>,
-    level: Arc<RwLock<u8>>
+    ob: Arc<Mutex<VecDeque<Output>>>
 }
 
 impl GameWrapper {
 
     pub fn new(h: GameHandle) -> GameWrapper {
+        return GameWrapper::with_listener(h, None);
+    }
+
+    /// As `new`, but every output is also copied to `listener`, tagged with
+    /// the game's id.
+    pub fn with_listener(h: GameHandle, listener: Option<(Uuid, Sender<(Uuid, Output)>)>) -> GameWrapper {
         log::debug!("Creating new GameWrapper!");
         let ob = Arc::new(Mutex::new(VecDeque::new()));
         let q = ob.clone();
         let rxo = h.output_receiver.clone();
-        let lvl: Arc<RwLock<u8>> = Arc::new(RwLock::new(1));
 
         thread::spawn(move || {
             let mut done = false;
@@ -359,6 +766,10 @@ impl GameWrapper {
             while !done {
                 match rxo.recv() {
                     Ok(evt) => {
+                        if let Some((id, listener)) = &listener {
+                            // the listener may have stopped listening
+                            let _ = listener.send((*id, evt.clone()));
+                        }
                         let mut q = q.lock().unwrap(); 
                         q.push_back(evt);
                     },
@@ -368,25 +779,17 @@ impl GameWrapper {
 
         });
         let txclock = h.input_sender.clone();
-        let clocklvl = lvl.clone();
+        // one tick per frame; the game works out gravity and lock delay
+        let sleep_time = 1000 / h.tick_rate.max(1) as u64;
         thread::spawn(move || {    
             // i *think* this lock is released after we send and check error
             // so it should be unlocked most of the time.        
-            while !txclock.lock().unwrap().send(Input::TickGame).is_err() {
+            while txclock.lock().unwrap().send(Input::TickGame).is_ok() {
                 log::debug!("Going to tick the game");
-                // level 10 = 100
-                // level 1 = 1000
-                // 1000 - (level) * 100
-                let t_lvl : u8;
-                {
-                    t_lvl = *clocklvl.read().unwrap();
-                }
-                let sleep_time = 1000 - t_lvl as u64 * 100;
-                log::debug!("sleeping for {:?}", sleep_time);
                 thread::sleep(time::Duration::from_millis(sleep_time));
             }
         });
-        return GameWrapper {h: h, ob: ob, level: lvl};
+        return GameWrapper {h: h, ob: ob};
     }
 
     pub fn drain(ob : Arc<Mutex<VecDeque<Output>>>) -> Vec<Output> {
@@ -408,39 +811,44 @@ impl GameWrapper {
     }
 
     pub fn set_level(&self, lvl: u8) {
-        let mut l = self.level.write().unwrap();
-        *l = lvl;
+        self.send(Input::SetLevel(lvl));
     }
 
     pub fn send(&self, input: Input) {
-        match self.h.input_sender.lock().unwrap().send(input) {
-            Err(e) => println!("ERROR {}", e),
-            _ => {}
+        if let Err(e) = self.h.input_sender.lock().unwrap().send(input) {
+            println!("ERROR {}", e);
         }
     }
 }
 
 
 pub struct GameMaster{
-    pool: Arc<RwLock<HashMap<Uuid, Arc<GameWrapper>>>>
+    pool: Arc<RwLock<HashMap<Uuid, Arc<GameWrapper>>>>,
+    matches: Arc<RwLock<HashMap<Uuid, Arc<Mutex<Versus>>>>>
+}
+
+impl Default for GameMaster {
+    fn default() -> GameMaster {
+        return GameMaster::new();
+    }
 }
 
 impl GameMaster {
     pub fn new() -> GameMaster {
         let v : HashMap<Uuid, Arc<GameWrapper>> = HashMap::new();
-        return GameMaster{pool: Arc::new(RwLock::new(v))};
+        return GameMaster{pool: Arc::new(RwLock::new(v)), matches: Arc::new(RwLock::new(HashMap::new()))};
     }
 
     pub fn count(&self) -> usize {
         return self.pool.read().unwrap().len();
     }
 
-    pub fn new_game(&self) -> Uuid {
+    pub fn new_game(&self, seed: u64, config: GameConfig) -> Uuid {
         // check for max games and don't allow us to make
         // more than that many.
         let uuid = Uuid::new_v4();
         let mut mut_pool = self.pool.write().unwrap();
-        mut_pool.insert(uuid, Arc::new(GameWrapper::new(game())));
+        mut_pool.insert(uuid, Arc::new(GameWrapper::new(game(seed, config))));
         return uuid;
     }
 
@@ -451,6 +859,62 @@ impl GameMaster {
         }
         return None;
     }
+
+    /// Starts a match with a game for each config, all dealt the same shapes
+    /// from `seed`.  Garbage one player sends goes to opponents picked by
+    /// their targeting, and every player is sent `Output::MatchOver` once
+    /// all but one have gone out; the winner's game plays on until ended.
+    /// The players' games are in the pool like any other.
+    pub fn new_match(&self, seed: u64, configs: Vec<GameConfig>, targeting: Targeting) -> Uuid {
+        let (tx, rx) = channel();
+        let games: Vec<(Uuid, Arc<GameWrapper>)> = configs.into_iter().map(|config| {
+            let uuid = Uuid::new_v4();
+            (uuid, Arc::new(GameWrapper::with_listener(game(seed, config), Some((uuid, tx.clone())))))
+        }).collect();
+        let ids: Vec<Uuid> = games.iter().map(|(id, _)| *id).collect();
+        let versus = Arc::new(Mutex::new(Versus::new(&ids, targeting, seed)));
+        {
+            let mut mut_pool = self.pool.write().unwrap();
+            for (uuid, gw) in games.iter() {
+                mut_pool.insert(*uuid, gw.clone());
+            }
+        }
+        let match_id = Uuid::new_v4();
+        self.matches.write().unwrap().insert(match_id, versus.clone());
+
+        thread::spawn(move || {
+            for (from, evt) in rx.iter() {
+                let mut versus = versus.lock().unwrap();
+                for (to, lines) in versus.observe(from, &evt) {
+                    if let Some((_, gw)) = games.iter().find(|(id, _)| *id == to) {
+                        gw.send(Input::Garbage(lines));
+                    }
+                }
+                if let Some(result) = versus.result() {
+                    for (_, gw) in games.iter() {
+                        gw.queue().lock().unwrap().push_back(Output::MatchOver(result.clone()));
+                    }
+                    break;
+                }
+            }
+        });
+        return match_id;
+    }
+
+    pub fn players(&self, m: Uuid) -> Option<Vec<Uuid>> {
+        return self.matches.read().unwrap().get(&m).map(|v| v.lock().unwrap().players());
+    }
+
+    pub fn set_targeting(&self, m: Uuid, player: Uuid, targeting: Targeting) -> bool {
+        return match self.matches.read().unwrap().get(&m) {
+            Some(v) => v.lock().unwrap().set_targeting(player, targeting),
+            None => false
+        };
+    }
+
+    pub fn match_result(&self, m: Uuid) -> Option<MatchResult> {
+        return self.matches.read().unwrap().get(&m).and_then(|v| v.lock().unwrap().result());
+    }
 }
 
 
@@ -458,20 +922,41 @@ impl GameMaster {
 
 
 #[cfg(test)]
+// the tests fail cases with assert!(false) from single match arms and
+// check for output with len() > 0
+#[allow(clippy::single_match, clippy::len_zero, clippy::assertions_on_constants)]
 mod tests {
     use crate::shape::Orientation;
+    use crate::randomizer::{RandomizerKind, Randomizer, Tgm};
+    use crate::rotation::RotationKind;
+    use crate::scoring::ScoringKind;
+    use crate::gravity::GravityCurve;
+    use crate::spin::Spin;
+    use crate::board::Cell;
+    use crate::garbage::{HolePattern, AttackTable};
     use super::*;
 
+    // the self play tests tick as fast as they can, so squeeze the clock down
+    // to half a row a tick and locking on the tick after landing
+    fn quick_config() -> GameConfig {
+        GameConfig { tick_rate: 2, ..GameConfig::default() }
+    }
+
+    // the kick tests below were written against the original rotation rules
+    fn legacy_game(tx: Sender<Output>) -> Game {
+        Game::with_config(tx, 0, GameConfig { rotation: RotationKind::Legacy, scoring: ScoringKind::Simple, ..GameConfig::default() })
+    }
+
     #[test]
     fn gw() {
-        let gw = GameWrapper::new(crate::game());
+        let gw = GameWrapper::new(crate::game(0, GameConfig::default()));
 
         assert_eq!(GameWrapper::drain(gw.queue()).len(), 0, "zero messages before start");
     }
 
     #[test]
     fn gw_buffer() {
-        let gw = GameWrapper::new(crate::game());
+        let gw = GameWrapper::new(crate::game(0, GameConfig::default()));
         assert_eq!(GameWrapper::drain(gw.queue()).len(), 0, "zero messages before start");
         gw.send(Input::StartGame);
         std::thread::sleep(time::Duration::from_millis(100));
@@ -489,30 +974,95 @@ mod tests {
     fn gm_new_game() {
         let gm = GameMaster::new();       
         assert_eq!(gm.count(), 0, "ran");
-        gm.new_game();
+        gm.new_game(0, GameConfig::default());
         assert_eq!(gm.count(), 1, "new game");
     }
 
+    #[test]
+    fn gm_match() {
+        let gm = GameMaster::new();
+        let m = gm.new_match(0, vec![quick_config(), quick_config()], Targeting::Random);
+        let players = gm.players(m).unwrap();
+        assert_eq!(players.len(), 2);
+        assert_eq!(gm.count(), 2, "the players' games are in the pool");
+        for p in players.iter() {
+            gm.game(*p).unwrap().send(Input::StartGame);
+        }
+        assert_eq!(gm.match_result(m), None);
+        gm.game(players[1]).unwrap().send(Input::EndGame);
+        std::thread::sleep(time::Duration::from_millis(100));
+        let result = gm.match_result(m).unwrap();
+        assert_eq!(result.winner, Some(players[0]));
+        assert_eq!(result.standings, players);
+        let outputs = GameWrapper::drain(gm.game(players[0]).unwrap().queue());
+        assert!(outputs.contains(&Output::MatchOver(result)), "every player hears the result");
+        gm.game(players[0]).unwrap().send(Input::EndGame);
+    }
+
+    #[test]
+    fn versus() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut v = Versus::new(&[a, b, c], Targeting::Even, 0);
+        assert_eq!(v.observe(a, &Output::GarbageSent(2)), vec![(b, 2)]);
+        assert_eq!(v.observe(a, &Output::GarbageSent(3)), vec![(c, 3)], "even spreads garbage about");
+        assert_eq!(v.observe(a, &Output::ScoreUpdate(100)), vec![]);
+
+        v.set_targeting(a, Targeting::KOs);
+        v.observe(c, &Output::PendingGarbage(3));
+        v.observe(b, &Output::PendingGarbage(1));
+        assert_eq!(v.observe(a, &Output::GarbageSent(1)), vec![(c, 1)], "KOs goes after the player in the most trouble");
+        let mut falling = Board::new();
+        falling.set(4, 22, Some(Shape::Tee.into()));
+        v.observe(b, &Output::BoardUpdate(falling));
+        assert_eq!(v.observe(a, &Output::GarbageSent(1)), vec![(c, 1)], "a shape still falling is not the stack");
+        let mut stacked = Board::new();
+        stacked.set(4, 3, Some(Shape::Tee.into()));
+        v.observe(b, &Output::ShapeLocked(Shape::Tee, stacked));
+        assert_eq!(v.observe(a, &Output::GarbageSent(1)), vec![(b, 1)], "a stack of four and one pending beats three pending");
+        v.observe(c, &Output::GarbageReceived(3));
+        v.observe(c, &Output::PendingGarbage(0));
+        assert_eq!(v.observe(a, &Output::GarbageSent(1)), vec![(b, 1)], "risen garbage counts as stack");
+        v.observe(c, &Output::GarbageReceived(3));
+        assert_eq!(v.observe(a, &Output::GarbageSent(1)), vec![(c, 1)]);
+
+        // b has been sent the least, so goes after a
+        assert_eq!(v.observe(b, &Output::GarbageSent(1)), vec![(a, 1)]);
+        v.set_targeting(c, Targeting::Attackers);
+        assert_eq!(v.observe(c, &Output::GarbageSent(2)), vec![(a, 2)], "only a is targeting c");
+        v.set_targeting(a, Targeting::Attackers);
+        assert_eq!(v.observe(a, &Output::GarbageSent(2)).len(), 2, "a is targeted by b and c");
+
+        v.observe(c, &Output::GameOver(Some(TopOut::GarbageOut)));
+        assert_eq!(v.result(), None);
+        assert_eq!(v.observe(c, &Output::GarbageSent(4)), vec![], "players who are out send nothing");
+        assert_eq!(v.observe(b, &Output::GarbageSent(4)), vec![(a, 4)]);
+        v.observe(b, &Output::GameOver(None));
+        assert!(v.is_over());
+        assert_eq!(v.result(), Some(MatchResult { winner: Some(a), standings: vec![a, b, c], kos: vec![(a, 2), (b, 0), (c, 0)] }));
+        assert_eq!(v.observe(a, &Output::GarbageSent(4)), vec![], "nothing is sent once the match is over");
+    }
+
     #[test]
     fn game() {
+        let mut rng = StdRng::seed_from_u64(0);
         // when the game starts, there should be a shape controller with the current shape
         // and there should be a next shape.  
         // there should be no "hold" shape
         let (tx, _rx) = channel();
-        let g = Game::new(tx);
+        let g = Game::new(tx, 0);
 
         match g.hold_shape {
             None => assert!(true),
             _ => assert!(false, "Hold shape should be unset at start")
         }
         let config = vec![
-            vec![None, Some(Shape::random()), None, Some(Shape::random()), None, None, Some(Shape::random())],
-            vec![None, Some(Shape::random()), None, Some(Shape::random()), None, None, Some(Shape::random())],
-            vec![None, Some(Shape::random()), None, Some(Shape::random()), None, None, Some(Shape::random())],
-            vec![None, Some(Shape::random()),  Some(Shape::random()), Some(Shape::random()), None, None, Some(Shape::random())],
-            vec![None, Some(Shape::random()), None, Some(Shape::random()), None, None, Some(Shape::random())],
-            vec![None, Some(Shape::random()), None, Some(Shape::random()), None, None, Some(Shape::random())],
-            vec![None, Some(Shape::random()), None, Some(Shape::random()), None, None, Some(Shape::random())],
+            vec![None, Some(Shape::random(&mut rng)), None, Some(Shape::random(&mut rng)), None, None, Some(Shape::random(&mut rng))],
+            vec![None, Some(Shape::random(&mut rng)), None, Some(Shape::random(&mut rng)), None, None, Some(Shape::random(&mut rng))],
+            vec![None, Some(Shape::random(&mut rng)), None, Some(Shape::random(&mut rng)), None, None, Some(Shape::random(&mut rng))],
+            vec![None, Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)), Some(Shape::random(&mut rng)), None, None, Some(Shape::random(&mut rng))],
+            vec![None, Some(Shape::random(&mut rng)), None, Some(Shape::random(&mut rng)), None, None, Some(Shape::random(&mut rng))],
+            vec![None, Some(Shape::random(&mut rng)), None, Some(Shape::random(&mut rng)), None, None, Some(Shape::random(&mut rng))],
+            vec![None, Some(Shape::random(&mut rng)), None, Some(Shape::random(&mut rng)), None, None, Some(Shape::random(&mut rng))],
         ];
         let mut board = g.board;
         board.setup(config, Point{x: 1, y: 3}, true);
@@ -520,7 +1070,7 @@ mod tests {
         let mut trues = 0;
         for y in 0..HEIGHT {
             for x in 0..WIDTH {
-                match board.0[y][x] {
+                match board.get(x, y) {
                     Some(_) => trues += 1,
                     None => {}
                 }
@@ -533,29 +1083,25 @@ mod tests {
     fn rotate() {
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
-        let mut b = g.board;
+        let mut g = legacy_game(tx);
+        let mut b = g.board.clone();
         
         g.shape_controller().set_shape(Shape::El);
         g.shape_controller().set_position(Point::new(3,3));
         g.start();
         g.rotate(Direction::Ccw);
-        {
-            let mat = g.shape_controller.shape().to_mat(g.shape_controller.orientation());
-            let pos = g.shape_controller.position();
-            b.occupy(&mat, pos);
-        }
-        assert!(b.0[3][3] != None);
-        assert!(b.0[3][4] != None);
-        assert!(b.0[3][5] != None);
-        assert!(b.0[4][5] != None);
+        g.shape_controller.occupy(&mut b);
+        assert!(b.get(3, 3) != None);
+        assert!(b.get(4, 3) != None);
+        assert!(b.get(5, 3) != None);
+        assert!(b.get(5, 4) != None);
     }
 
     #[test]
     fn wall_kick_l() {
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         g.shape_controller().set_shape(Shape::El);
         g.shape_controller().set_position(Point::new(0, 3));
         g.start();
@@ -567,116 +1113,86 @@ mod tests {
     fn flush_wall_r() {
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         g.shape_controller.set_shape(Shape::El);
         g.shape_controller.set_position(Point::new(8, 3));
         g.shape_controller.set_orientation(Orientation::Up);
         g.start();
-        let mut b = g.board;
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
-        assert!(b.0[3][8] != None, "box 1 in the wrong spot!");
-        assert!(b.0[4][8] != None);
-        assert!(b.0[5][8] != None);
-        assert!(b.0[3][9] != None);
+        let mut b = g.board.clone();
+        g.shape_controller.occupy(&mut b);
+        assert!(b.get(8, 3) != None, "box 1 in the wrong spot!");
+        assert!(b.get(8, 4) != None);
+        assert!(b.get(8, 5) != None);
+        assert!(b.get(9, 3) != None);
     }
 
     #[test]
     fn flush_wall_r2() {
         let (tx, _rx) = channel();
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         g.shape_controller.set_shape(Shape::Eye);
         g.shape_controller.set_position(Point::new(5, 3));
         g.shape_controller.set_orientation(Orientation::Up);
         g.start();
-        let mut b = g.board;
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        let mut b = g.board.clone();
+        g.shape_controller.occupy(&mut b);
         println!("{}",b.report());
-        assert!(b.0[3][5] != None);
-        assert!(b.0[4][5] != None);
-        assert!(b.0[5][5] != None);
-        assert!(b.0[6][5] != None);
+        assert!(b.get(5, 3) != None);
+        assert!(b.get(5, 4) != None);
+        assert!(b.get(5, 5) != None);
+        assert!(b.get(5, 6) != None);
 
         
-        b.vacate(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.vacate(&mut b);
         
         g.shape_controller.right(&g.board);
         
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.occupy(&mut b);
 
         println!("{}",b.report());
-        assert!(b.0[3][6] != None, "not at y = 6");
-        assert!(b.0[4][6] != None);
-        assert!(b.0[5][6] != None);
-        assert!(b.0[6][6] != None);
+        assert!(b.get(6, 3) != None, "not at y = 6");
+        assert!(b.get(6, 4) != None);
+        assert!(b.get(6, 5) != None);
+        assert!(b.get(6, 6) != None);
 
          
-        b.vacate(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.vacate(&mut b);
         
         g.shape_controller.right(&g.board);
         
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.occupy(&mut b);
 
         println!("{}",b.report());
-        assert!(b.0[3][7] != None, "not at y = 7");
-        assert!(b.0[4][7] != None);
-        assert!(b.0[5][7] != None);
-        assert!(b.0[6][7] != None);
+        assert!(b.get(7, 3) != None, "not at y = 7");
+        assert!(b.get(7, 4) != None);
+        assert!(b.get(7, 5) != None);
+        assert!(b.get(7, 6) != None);
 
 
-        b.vacate(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.vacate(&mut b);
         
         g.shape_controller.right(&g.board);
         
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.occupy(&mut b);
 
         println!("{}",b.report());
-        assert!(b.0[3][8] != None, "not at y = 8");
-        assert!(b.0[4][8] != None);
-        assert!(b.0[5][8] != None);
-        assert!(b.0[6][8] != None);
+        assert!(b.get(8, 3) != None, "not at y = 8");
+        assert!(b.get(8, 4) != None);
+        assert!(b.get(8, 5) != None);
+        assert!(b.get(8, 6) != None);
 
         
-        b.vacate(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.vacate(&mut b);
         
         g.shape_controller.right(&g.board);
         
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.occupy(&mut b);
 
         println!("{}",b.report());
-        assert!(b.0[3][9] != None, "not at y = 9");
-        assert!(b.0[4][9] != None);
-        assert!(b.0[5][9] != None);
-        assert!(b.0[6][9] != None);
+        assert!(b.get(9, 3) != None, "not at y = 9");
+        assert!(b.get(9, 4) != None);
+        assert!(b.get(9, 5) != None);
+        assert!(b.get(9, 6) != None);
 
 
     }
@@ -685,20 +1201,17 @@ mod tests {
     fn wall_kick_r() {
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         g.shape_controller.set_shape(Shape::El);
         g.shape_controller.set_position(Point::new(8, 3));
         g.shape_controller.set_orientation(Orientation::Up);
         g.start();
-        let mut b = g.board;
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );      
-        assert!(b.0[3][8] != None);
-        assert!(b.0[4][8] != None);
-        assert!(b.0[5][8] != None);
-        assert!(b.0[3][9] != None);        
+        let mut b = g.board.clone();
+        g.shape_controller.occupy(&mut b);      
+        assert!(b.get(8, 3) != None);
+        assert!(b.get(8, 4) != None);
+        assert!(b.get(8, 5) != None);
+        assert!(b.get(9, 3) != None);        
 
         g.rotate(Direction::Ccw);
         
@@ -709,21 +1222,18 @@ mod tests {
     fn wall_kick_eye_r() {
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         g.shape_controller.set_shape(Shape::Eye);
         g.shape_controller.set_position(Point::new(9, 3));
         g.shape_controller.set_orientation(Orientation::Up);
         g.start();
-        let mut b = g.board;
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        let mut b = g.board.clone();
+        g.shape_controller.occupy(&mut b);
 
-        assert!(b.0[3][9] != None);
-        assert!(b.0[4][9] != None);
-        assert!(b.0[5][9] != None);
-        assert!(b.0[6][9] != None);        
+        assert!(b.get(9, 3) != None);
+        assert!(b.get(9, 4) != None);
+        assert!(b.get(9, 5) != None);
+        assert!(b.get(9, 6) != None);        
 
         g.rotate(Direction::Ccw);
         
@@ -735,7 +1245,7 @@ mod tests {
 
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         g.shape_controller.set_shape(Shape::Eye);
         g.shape_controller.set_position(Point::new(7, 3));
         g.shape_controller.set_orientation(Orientation::Up);
@@ -753,32 +1263,23 @@ mod tests {
         ];        
         g.board.setup(config, Point::new(0,0), false);
         g.start();
-        let mut b = g.board;
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        let mut b = g.board.clone();
+        g.shape_controller.occupy(&mut b);
         println!("{}", b.report());
-        assert!(b.0[3][7] != None);
-        assert!(b.0[4][7] != None);
-        assert!(b.0[5][7] != None);
-        assert!(b.0[6][7] != None);
-
-        b.vacate(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        assert!(b.get(7, 3) != None);
+        assert!(b.get(7, 4) != None);
+        assert!(b.get(7, 5) != None);
+        assert!(b.get(7, 6) != None);
+
+        g.shape_controller.vacate(&mut b);
         g.rotate(Direction::Ccw);
         
         assert!(g.shape_controller().position().x == 4, "expected kick on shape"); 
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.occupy(&mut b);
         println!("{}", b.report());
-        assert!(b.0[3][9].unwrap() == Shape::ElInv, "Should be ElInv! But was {:?}", b.0[3][9].unwrap());
-        assert!(b.0[3][8].unwrap() == Shape::ElInv, "Should be ElInv! But was {:?}", b.0[3][8].unwrap());
-        assert!(b.0[3][7].unwrap() == Shape::Eye, "Should be ElInv! But was {:?}", b.0[3][7].unwrap());
+        assert!(b.get(9, 3).unwrap() == Cell::Shape(Shape::ElInv), "Should be ElInv! But was {:?}", b.get(9, 3).unwrap());
+        assert!(b.get(8, 3).unwrap() == Cell::Shape(Shape::ElInv), "Should be ElInv! But was {:?}", b.get(8, 3).unwrap());
+        assert!(b.get(7, 3).unwrap() == Cell::Shape(Shape::Eye), "Should be ElInv! But was {:?}", b.get(7, 3).unwrap());
         
         
 
@@ -786,20 +1287,21 @@ mod tests {
 
     #[test]
     fn internal_kick_r() {
+        let mut rng = StdRng::seed_from_u64(0);
         // set up the game, put some junk in the board
         // kick off the junk.
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         let config = vec![
-            vec![None, None, None, None, Some(Shape::random())],
-            vec![None, None, None, None, Some(Shape::random())],
-            vec![None, None, None, None, Some(Shape::random())],
-            vec![None, None, None, None, Some(Shape::random())],
-            vec![None, None, None, None, Some(Shape::random())],
-            vec![None, None, None, None, Some(Shape::random())],
-            vec![None, None, None, None, Some(Shape::random())],
-            vec![None, None, None, None, Some(Shape::random())],
+            vec![None, None, None, None, Some(Shape::random(&mut rng))],
+            vec![None, None, None, None, Some(Shape::random(&mut rng))],
+            vec![None, None, None, None, Some(Shape::random(&mut rng))],
+            vec![None, None, None, None, Some(Shape::random(&mut rng))],
+            vec![None, None, None, None, Some(Shape::random(&mut rng))],
+            vec![None, None, None, None, Some(Shape::random(&mut rng))],
+            vec![None, None, None, None, Some(Shape::random(&mut rng))],
+            vec![None, None, None, None, Some(Shape::random(&mut rng))],
         ];
         g.board.setup(config, Point::new(0,0), false);
         g.shape_controller.set_shape(Shape::El);
@@ -814,18 +1316,19 @@ mod tests {
 
     #[test]
     fn t_spin() {
+        let mut rng = StdRng::seed_from_u64(0);
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         let config = vec![
             vec![None, None, None, None, None, None, None, None, None, None],
             vec![None, None, None, None, None, None, None, None, None, None],
             vec![None, None, None, None, None, None, None, None, None, None],
             vec![None, None, None, None, None, None, None, None, None, None],
-            vec![None, None, None, None, Some(Shape::random()), Some(Shape::random()),  None,  None, None, None],
-            vec![None, None, None, None, Some(Shape::random()), Some(Shape::random()),  None,  None, None, Some(Shape::random())],
-            vec![Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  None, Some(Shape::random()), Some(Shape::random()),  None, None, None, Some(Shape::random())],
-            vec![Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()), Some(Shape::random()),  Some(Shape::random()),  None, Some(Shape::random()),  Some(Shape::random())],
+            vec![None, None, None, None, Some(Shape::random(&mut rng)), Some(Shape::random(&mut rng)),  None,  None, None, None],
+            vec![None, None, None, None, Some(Shape::random(&mut rng)), Some(Shape::random(&mut rng)),  None,  None, None, Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)), Some(Shape::random(&mut rng)),  None, None, None, Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)), Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
         ];
         g.board.setup(config, Point::new(0,0), false);
         g.shape_controller.set_shape(Shape::Tee);
@@ -838,18 +1341,19 @@ mod tests {
 
     #[test]
     fn kick_up() {
+        let mut rng = StdRng::seed_from_u64(0);
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         let config = vec![
             vec![None, None, None, None, None, None, None,  None, None, None],
             vec![None, None, None, None, None, None, None,  None, None, None],
             vec![None, None, None, None, None, None, None,  None, None, None],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),  None, None,  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),  None,  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),  None,  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),  None,  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),  None,  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),  None, None,  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),  None,  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),  None,  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),  None,  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),  None,  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
         ];
         g.board.setup(config, Point::new(0,0), false);
         g.shape_controller.set_shape(Shape::Eye);
@@ -864,28 +1368,942 @@ mod tests {
 
     }
 
+    fn piece_cells(g: &Game) -> Vec<(usize, usize)> {
+        let mat = g.shape_controller.mat();
+        let p = g.shape_controller.position();
+        let mut cells = Vec::new();
+        for y in 0..4 {
+            for x in 0..4 {
+                if mat[3 - y][x] != None {
+                    cells.push((p.x + x, p.y + y));
+                }
+            }
+        }
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn srs_spawn() {
+        let (tx, _rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.shape_controller = ShapeState::new_from_shape(Shape::Tee, RotationKind::Srs.build(), WIDTH, 20);
+        assert_eq!(piece_cells(&g), vec![(3, 21), (4, 21), (4, 22), (5, 21)]);
+        g.shape_controller = ShapeState::new_from_shape(Shape::Eye, RotationKind::Srs.build(), WIDTH, 20);
+        assert_eq!(piece_cells(&g), vec![(3, 21), (4, 21), (5, 21), (6, 21)]);
+        g.shape_controller = ShapeState::new_from_shape(Shape::Square, RotationKind::Srs.build(), WIDTH, 20);
+        assert_eq!(piece_cells(&g), vec![(4, 21), (4, 22), (5, 21), (5, 22)]);
+    }
+
+    #[test]
+    fn srs_rotates_about_centre() {
+        let (tx, _rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(3, 5));
+        g.start();
+        let start = piece_cells(&g);
+        assert_eq!(start, vec![(3, 5), (4, 5), (4, 6), (5, 5)]);
+        g.rotate(Direction::Cw);
+        assert_eq!(piece_cells(&g), vec![(4, 4), (4, 5), (4, 6), (5, 5)], "T should pivot on its centre");
+        g.rotate(Direction::Cw);
+        g.rotate(Direction::Cw);
+        g.rotate(Direction::Cw);
+        assert_eq!(piece_cells(&g), start, "four turns should come back to the start");
+
+        g.shape_controller = ShapeState::new_from_shape(Shape::Square, RotationKind::Srs.build(), WIDTH, 20);
+        let start = piece_cells(&g);
+        g.rotate(Direction::Ccw);
+        assert_eq!(piece_cells(&g), start, "the square should not move");
+    }
+
+    #[test]
+    fn srs_kick_right() {
+        let (tx, _rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_orientation(Orientation::Right);
+        g.shape_controller.set_position(Point::new(0, 5));
+        g.start();
+        assert_eq!(piece_cells(&g), vec![(0, 5), (0, 6), (0, 7), (1, 6)]);
+        g.rotate(Direction::Cw);
+        assert_eq!(piece_cells(&g), vec![(0, 6), (1, 5), (1, 6), (2, 6)], "expected a kick off the left wall");
+    }
+
+    #[test]
+    fn srs_eye_wall_kick() {
+        let (tx, _rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.shape_controller.set_shape(Shape::Eye);
+        g.shape_controller.set_orientation(Orientation::Right);
+        g.shape_controller.set_position(Point::new(9, 5));
+        g.start();
+        assert_eq!(piece_cells(&g), vec![(9, 5), (9, 6), (9, 7), (9, 8)]);
+        g.rotate(Direction::Cw);
+        assert_eq!(piece_cells(&g), vec![(6, 6), (7, 6), (8, 6), (9, 6)], "expected a kick off the right wall");
+    }
+
+    #[test]
+    fn srs_t_spin_triple() {
+        let (tx, _rx) = channel();
+        let mut g = Game::new(tx, 0);
+        for y in 0..3 {
+            for x in 0..WIDTH {
+                g.board.set(x, y, Some(Shape::Zee.into()));
+            }
+        }
+        for (x, y) in [(3, 2), (3, 1), (4, 1), (3, 0)].iter() {
+            g.board.set(*x, *y, None);
+        }
+        g.board.set(3, 4, Some(Shape::Zee.into()));
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(3, 3));
+        g.start();
+        assert_eq!(piece_cells(&g), vec![(3, 3), (4, 3), (4, 4), (5, 3)]);
+        g.rotate(Direction::Cw);
+        assert_eq!(piece_cells(&g), vec![(3, 0), (3, 1), (3, 2), (4, 1)], "expected the last kick into the slot");
+    }
+
+    #[test]
+    fn t_spin_triple_scores() {
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        for y in 0..3 {
+            for x in 0..WIDTH {
+                g.board.set(x, y, Some(Shape::Zee.into()));
+            }
+        }
+        for (x, y) in [(3, 2), (3, 1), (4, 1), (3, 0)].iter() {
+            g.board.set(*x, *y, None);
+        }
+        g.board.set(3, 4, Some(Shape::Zee.into()));
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(3, 3));
+        g.start();
+        g.next(Input::Cw);
+        g.next(Input::Drop);
+        let outputs: Vec<Output> = rx.try_iter().collect();
+        assert!(outputs.contains(&Output::TSpin(Spin::Full, 3)), "the far kick makes this a full T-spin");
+        assert!(outputs.contains(&Output::Scored(ScoreReason::TSpin(Spin::Full, 3), 1600)));
+        assert_eq!(g.score, 1600);
+    }
+
+    #[test]
+    fn t_spin_mini() {
+        let t_slot = |rotate: bool| {
+            let (tx, rx) = channel();
+            let mut g = Game::new(tx, 0);
+            for (x, y) in [(4, 0), (6, 0), (4, 2)].iter() {
+                g.board.set(*x, *y, Some(Shape::Zee.into()));
+            }
+            g.shape_controller.set_shape(Shape::Tee);
+            g.start();
+            if rotate {
+                g.shape_controller.set_position(Point::new(4, 1));
+                g.next(Input::Cw);
+            } else {
+                g.shape_controller.set_orientation(Orientation::Right);
+                g.shape_controller.set_position(Point::new(5, 0));
+            }
+            g.next(Input::Drop);
+            rx.try_iter().find_map(|o| match o {
+                Output::TSpin(spin, lines) => Some((spin, lines)),
+                _ => None
+            })
+        };
+        assert_eq!(t_slot(true), Some((Spin::Mini, 0)), "one corner by the point is open");
+        assert_eq!(t_slot(false), None, "only a shape that turned into place has spun");
+    }
+
+    #[test]
+    fn srs_blocked_rotation() {
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        for y in 0..8 {
+            for x in 0..WIDTH {
+                if x != 4 {
+                    g.board.set(x, y, Some(Shape::Zee.into()));
+                }
+            }
+        }
+        g.shape_controller.set_shape(Shape::Eye);
+        g.shape_controller.set_orientation(Orientation::Right);
+        g.shape_controller.set_position(Point::new(4, 0));
+        g.start();
+        let start = piece_cells(&g);
+        g.rotate(Direction::Ccw);
+        assert_eq!(piece_cells(&g), start, "nowhere to kick, so the shape should stay put");
+        assert_eq!(g.shape_controller.orientation(), Orientation::Right);
+        g.next(Input::Cw);
+        assert_eq!(g.shape_controller.orientation(), Orientation::Right);
+        assert!(!rx.try_iter().any(|o| matches!(o, Output::RotatedShape(_))), "a blocked turn is not reported");
+    }
+
+    #[test]
+    fn rotation_systems_turn_in_place() {
+        let b = Board::new();
+        for kind in [RotationKind::Srs, RotationKind::Ars, RotationKind::Nes, RotationKind::Legacy].iter() {
+            let system = kind.build();
+            for shape in Shape::ALL.iter() {
+                let mut state = ShapeState::new_from_shape(*shape, system.clone(), WIDTH, 20);
+                state.set_position(Point::new(4, 10));
+                let start = *state.position();
+                for _ in 0..4 {
+                    let m = state.mat();
+                    let count = m.iter().flatten().filter(|c| **c != None).count();
+                    assert_eq!(count, 4, "{:?} {:?} should have four cells", kind, shape);
+                    assert!(state.rotate(Direction::Cw, &b), "{:?} {:?} should turn in open space", kind, shape);
+                }
+                assert_eq!(*state.position(), start, "{:?} {:?} should come back to where it started", kind, shape);
+                let spawn = ShapeState::new_from_shape(*shape, system.clone(), WIDTH, 20);
+                assert!(!spawn.shape_collide(&b), "{:?} {:?} should spawn on the board", kind, shape);
+            }
+        }
+    }
+
+    #[test]
+    fn ars_kick() {
+        let (tx, _rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { rotation: RotationKind::Ars, ..GameConfig::default() });
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_orientation(Orientation::Left);
+        g.shape_controller.set_position(Point::new(0, 5));
+        g.start();
+        assert_eq!(piece_cells(&g), vec![(0, 5), (0, 6), (0, 7), (1, 6)]);
+        g.rotate(Direction::Cw);
+        assert_eq!(piece_cells(&g), vec![(0, 6), (1, 5), (1, 6), (2, 6)], "expected a kick off the left wall");
+    }
+
+    #[test]
+    fn ars_centre_column() {
+        let (tx, _rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { rotation: RotationKind::Ars, ..GameConfig::default() });
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(3, 5));
+        g.board.set(4, 7, Some(Shape::Zee.into()));
+        g.start();
+        let start = piece_cells(&g);
+        g.rotate(Direction::Cw);
+        assert_eq!(piece_cells(&g), start, "a block over the middle column should stop the turn");
+        assert_eq!(g.shape_controller.orientation(), Orientation::Up);
+    }
+
+    #[test]
+    fn nes_no_kick() {
+        let (tx, _rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { rotation: RotationKind::Nes, ..GameConfig::default() });
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_orientation(Orientation::Left);
+        g.shape_controller.set_position(Point::new(0, 5));
+        g.start();
+        let start = piece_cells(&g);
+        g.rotate(Direction::Cw);
+        assert_eq!(piece_cells(&g), start, "nes should never kick off the wall");
+        assert_eq!(g.shape_controller.orientation(), Orientation::Left);
+    }
+
+    #[test]
+    fn srs_rotate_180() {
+        let (tx, _rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(3, 5));
+        g.start();
+        g.rotate(Direction::Half);
+        assert_eq!(g.shape_controller.orientation(), Orientation::Down);
+        assert_eq!(piece_cells(&g), vec![(3, 5), (4, 4), (4, 5), (5, 5)], "T should flip about its centre");
+
+        // flipping on the floor has to kick up a row
+        g.shape_controller.set_orientation(Orientation::Up);
+        g.shape_controller.set_position(Point::new(3, 0));
+        g.rotate(Direction::Half);
+        assert_eq!(g.shape_controller.orientation(), Orientation::Down);
+        assert_eq!(piece_cells(&g), vec![(3, 1), (4, 0), (4, 1), (5, 1)]);
+    }
+
+    #[test]
+    fn rotate_180_input() {
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.shape_controller.set_shape(Shape::El);
+        g.shape_controller.set_position(Point::new(3, 10));
+        g.start();
+        rx.try_iter().count();
+        g.next(Input::Rotate180);
+        let log: Vec<Output> = rx.try_iter().collect();
+        assert!(log.contains(&Output::RotatedShape(Orientation::Down)), "expected a rotation event");
+        assert!(log.iter().any(|o| matches!(o, Output::ShapePosition(Shape::El, Some(Orientation::Up), Orientation::Down, _, _))),
+            "expected the shape position to report the flip");
+    }
+
+    fn locked(rx: &Receiver<Output>) -> bool {
+        rx.try_iter().any(|o| matches!(o, Output::ShapeLocked(_, _)))
+    }
+
+    #[test]
+    fn lock_delay() {
+        let (tx, rx) = channel();
+        // 500ms at 60 ticks a second is 30 ticks on the ground
+        let mut g = Game::new(tx, 0);
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(3, 0));
+        g.start();
+        for _ in 0..29 {
+            g.next(Input::TickGame);
+        }
+        assert!(!locked(&rx), "should not lock before the delay runs out");
+        g.next(Input::TickGame);
+        assert!(locked(&rx), "should lock once the delay runs out");
+
+        let (tx, rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { tick_rate: 120, ..GameConfig::default() });
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(3, 0));
+        g.start();
+        for _ in 0..59 {
+            g.next(Input::TickGame);
+        }
+        assert!(!locked(&rx), "the delay is time, not ticks");
+        g.next(Input::TickGame);
+        assert!(locked(&rx));
+    }
+
+    #[test]
+    fn lock_delay_resets() {
+        let (tx, rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { lock_resets: 2, ..GameConfig::default() });
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(3, 0));
+        g.start();
+        for _ in 0..20 {
+            g.next(Input::TickGame);
+        }
+        g.next(Input::Left);
+        for _ in 0..20 {
+            g.next(Input::TickGame);
+        }
+        g.next(Input::Right);
+        for _ in 0..20 {
+            g.next(Input::TickGame);
+        }
+        assert!(!locked(&rx), "moving should have restarted the delay twice");
+        g.next(Input::Left);
+        for _ in 0..9 {
+            g.next(Input::TickGame);
+        }
+        assert!(!locked(&rx));
+        g.next(Input::TickGame);
+        assert!(locked(&rx), "out of resets, so the delay should run out");
+    }
+
+    #[test]
+    fn spinning_locks() {
+        // turning on the floor can kick a shape up a row, but landing back
+        // on the same row must not restart the delay
+        for shape in Shape::ALL.iter() {
+            let (tx, rx) = channel();
+            let mut g = Game::new(tx, 0);
+            g.shape_controller.set_shape(*shape);
+            g.start();
+            let mut spins = 0;
+            while !locked(&rx) {
+                assert!(spins < 2000, "{:?} should lock however it is turned", shape);
+                g.next(match spins % 2 {
+                    0 => Input::Cw,
+                    _ => Input::Ccw
+                });
+                for _ in 0..4 {
+                    g.next(Input::TickGame);
+                }
+                spins += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn hard_drop_locks() {
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.start();
+        g.next(Input::Drop);
+        assert!(locked(&rx), "a hard drop should lock straight away");
+    }
+
+    #[test]
+    fn ghost_position() {
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        for y in 0..5 {
+            g.board.set(4, y, Some(Shape::Zee.into()));
+        }
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(3, 21));
+        let last_ghost = |rx: &Receiver<Output>| rx.try_iter().filter_map(|o| match o {
+            Output::GhostPosition(_, _, p) => Some(p),
+            _ => None
+        }).last();
+        g.start();
+        assert_eq!(last_ghost(&rx), Some(Point::new(3, 5)), "should land on the column");
+        g.next(Input::Left);
+        assert_eq!(last_ghost(&rx), Some(Point::new(2, 5)));
+        g.next(Input::Left);
+        assert_eq!(last_ghost(&rx), Some(Point::new(1, 0)), "clear of the column it reaches the floor");
+        g.next(Input::Drop);
+        assert!(g.board.get(1, 0) != None && g.board.get(3, 0) != None, "the shape should land on its ghost");
+        assert_eq!(last_ghost(&rx), Some(g.shape_controller.ghost(&g.board)), "the next shape should get a ghost of its own");
+    }
+
+    #[test]
+    fn bitboard() {
+        let mut b = Board::new();
+        b.set(0, 0, Some(Shape::Eye.into()));
+        b.set(3, 0, Some(Shape::Tee.into()));
+        assert_eq!(b.row(0), 0b1001);
+        assert_eq!(b.get(3, 0), Some(Cell::Shape(Shape::Tee)), "colours live alongside the bits");
+        b.set(3, 0, None);
+        assert_eq!(b.row(0), 0b1);
+
+        b.fill_row(1, Some(Shape::Zee.into()));
+        b.set(5, 2, Some(Shape::El.into()));
+        assert_eq!(b.clear_full_rows(), 1);
+        assert_eq!(b.row(1), 1 << 5, "rows above drop into the gap");
+        assert_eq!(b.get(5, 1), Some(Cell::Shape(Shape::El)));
+        assert_eq!(b.row(b.height() - 1), 0);
+
+        let wide = Board::with_size(64, 10);
+        let m = [0b1111, 0, 0, 0];
+        assert!(!wide.collides(&m, &Point::new(60, 0)));
+        assert!(wide.collides(&m, &Point::new(61, 0)), "should hang off the right edge");
+
+        // the masks worked out up front match the shape matrices
+        for kind in [RotationKind::Srs, RotationKind::Ars, RotationKind::Nes, RotationKind::Legacy].iter() {
+            let system = kind.build();
+            for shape in Shape::ALL.iter() {
+                for o in [Orientation::Up, Orientation::Right, Orientation::Down, Orientation::Left].iter() {
+                    let mat = system.mat(*shape, *o);
+                    let rows = system.rows(*shape, *o);
+                    for y in 0..4 {
+                        for x in 0..4 {
+                            assert_eq!(mat[3 - y][x] != None, rows[y] & 1 << x != 0, "{:?} {:?} {:?}", kind, shape, o);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn garbage() {
+        let (tx, rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { garbage_holes: HolePattern::Column(2), ..GameConfig::default() });
+        g.board.set(5, 0, Some(Shape::Zee.into()));
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(6, 5));
+        g.start();
+        g.next(Input::Garbage(3));
+        assert_eq!(g.board.row(0), 1 << 5, "garbage waits for the next lock");
+        assert_eq!(g.pending_garbage(), 3);
+        g.next(Input::Drop);
+        for y in 0..3 {
+            assert_eq!(g.board.row(y), 0b11_1111_1011, "every garbage row should be full bar the hole");
+        }
+        assert_eq!(g.board.get(0, 0), Some(Cell::Garbage));
+        assert_eq!(g.board.get(5, 3), Some(Cell::Shape(Shape::Zee)), "the stack should be pushed up");
+        assert_eq!(g.pending_garbage(), 0);
+        let outputs: Vec<Output> = rx.try_iter().filter(|o| matches!(o, Output::PendingGarbage(_) | Output::GarbageReceived(_))).collect();
+        assert_eq!(outputs, vec![Output::PendingGarbage(3), Output::GarbageReceived(3), Output::PendingGarbage(0)]);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let cheese = HolePattern::Cheese.holes(10, WIDTH, &mut rng);
+        assert!(cheese.iter().any(|x| *x != cheese[0]), "cheese should move the hole about");
+        let clean = HolePattern::Clean.holes(10, WIDTH, &mut rng);
+        assert!(clean.iter().all(|x| *x == clean[0]), "a clean batch keeps one hole");
+
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.board.set(0, HEIGHT - 1, Some(Shape::Zee.into()));
+        g.shape_controller.set_shape(Shape::Square);
+        g.shape_controller.set_position(Point::new(0, 5));
+        g.start();
+        g.next(Input::Garbage(1));
+        g.next(Input::Drop);
+        assert!(rx.try_iter().any(|o| o == Output::GameOver(Some(TopOut::GarbageOut))));
+        assert_eq!(g.state, GameState::Over);
+    }
+
+    #[test]
+    fn garbage_before_start() {
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.next(Input::Garbage(2));
+        assert_eq!(g.pending_garbage(), 2, "garbage sent before start waits for the game");
+        g.start();
+        g.next(Input::Drop);
+        assert_eq!(g.board.garbage_rows(), 2);
+        assert!(rx.try_iter().any(|o| o == Output::GarbageReceived(2)));
+        g.quit();
+        g.next(Input::Garbage(2));
+        assert_eq!(g.pending_garbage(), 0, "a finished game takes no more");
+    }
+
+    #[test]
+    fn garbage_cancels() {
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        // keeps the clears from being perfect clears
+        g.board.set(0, 20, Some(Shape::Zee.into()));
+        g.start();
+        g.next(Input::Garbage(5));
+        let tetris = |g: &mut Game| {
+            for y in 0..4 {
+                g.board.fill_row(y, Some(Shape::Zee.into()));
+                g.board.set(0, y, None);
+            }
+            g.shape_controller.set_shape(Shape::Eye);
+            g.shape_controller.set_orientation(Orientation::Right);
+            g.shape_controller.set_position(Point::new(0, 10));
+            g.next(Input::Drop);
+        };
+        tetris(&mut g);
+        assert_eq!(g.pending_garbage(), 1, "a tetris cancels four lines");
+        assert!(g.board.row(0) & 1 << 9 == 0, "a clear keeps the rest from rising");
+        tetris(&mut g);
+        assert_eq!(g.pending_garbage(), 0);
+        let outputs: Vec<Output> = rx.try_iter().filter(|o| matches!(o, Output::PendingGarbage(_) | Output::GarbageSent(_))).collect();
+        // back to back and a combo make six, one of which cancels the last pending line
+        assert_eq!(outputs, vec![Output::PendingGarbage(5), Output::PendingGarbage(1), Output::PendingGarbage(0), Output::GarbageSent(5)]);
+    }
+
+    #[test]
+    fn attack_table() {
+        let table = AttackTable::default();
+        let clear = |lines: u8, spin: Option<Spin>| Clear { lines: lines, spin: spin, combo: 1, back_to_back: 0, perfect: false };
+        assert_eq!(table.attack(&clear(1, None)), 0);
+        assert_eq!(table.attack(&clear(2, None)), 1);
+        assert_eq!(table.attack(&clear(4, None)), 4);
+        assert_eq!(table.attack(&clear(2, Some(Spin::Full))), 4);
+        assert_eq!(table.attack(&clear(1, Some(Spin::Mini))), 0);
+        assert_eq!(table.attack(&Clear { back_to_back: 2, ..clear(4, None) }), 5);
+        assert_eq!(table.attack(&Clear { combo: 5, ..clear(1, None) }), 2);
+        assert_eq!(table.attack(&Clear { combo: 30, ..clear(1, None) }), 5, "the combo table tops out");
+        assert_eq!(table.attack(&Clear { perfect: true, ..clear(1, None) }), 10);
+        assert_eq!(table.attack(&clear(0, Some(Spin::Full))), 0);
+
+        let table = AttackTable { lines: vec![0, 2, 3, 6], combo: vec![], ..AttackTable::default() };
+        assert_eq!(table.attack(&clear(4, None)), 6);
+        assert_eq!(table.attack(&Clear { combo: 5, ..clear(1, None) }), 0, "an empty combo table sends nothing extra");
+    }
+
+    #[test]
+    fn board_sizes() {
+        for (width, height) in [(4, 20), (20, 20), (10, 40)].iter() {
+            for rotation in [RotationKind::Srs, RotationKind::Ars, RotationKind::Nes, RotationKind::Legacy].iter() {
+                for shape in Shape::ALL.iter() {
+                    let (tx, rx) = channel();
+                    let config = GameConfig { width: *width, height: *height, rotation: *rotation, ..GameConfig::default() };
+                    let mut g = Game::with_config(tx, 0, config);
+                    g.spawn(*shape);
+                    let cells = piece_cells(&g);
+                    assert!(cells.iter().all(|(x, _)| x < width), "{:?} {:?} should spawn inside a board {} wide", rotation, shape, width);
+                    assert_eq!(cells.iter().map(|(_, y)| *y).min(), Some(height + 1), "{:?} should spawn just above the field", shape);
+                    g.start();
+                    g.next(Input::Drop);
+                    assert!(locked(&rx), "{:?} should drop and lock", shape);
+                    assert_eq!(g.state, GameState::Playing);
+                }
+            }
+        }
+        let (tx, _rx) = channel();
+        let g = Game::with_config(tx, 0, GameConfig { width: 20, height: 30, ..GameConfig::default() });
+        assert_eq!((g.board.width(), g.board.height()), (20, 35));
+    }
+
+    #[test]
+    fn minimum_vanish_zone() {
+        let expected = [(RotationKind::Srs, 3), (RotationKind::Ars, 4), (RotationKind::Nes, 4), (RotationKind::Legacy, 5)];
+        for (rotation, rows) in expected.iter() {
+            assert_eq!(rotation.build().vanish_zone(), *rows, "{:?}", rotation);
+            for shape in Shape::ALL.iter() {
+                let (tx, rx) = channel();
+                let mut g = Game::with_config(tx, 0, GameConfig { rotation: *rotation, vanish_zone: 0, ..GameConfig::default() });
+                assert_eq!(g.board.height(), 20 + rows, "a smaller vanish zone is raised to what {:?} needs", rotation);
+                g.spawn(*shape);
+                g.start();
+                for turn in [Input::Cw, Input::Cw, Input::Rotate180, Input::Ccw, Input::Ccw, Input::Cw] {
+                    g.next(turn);
+                    assert!(piece_cells(&g).iter().all(|(_, y)| *y < g.board.height()), "{:?} {:?} should turn inside the board", rotation, shape);
+                }
+                g.next(Input::Drop);
+                assert!(locked(&rx), "{:?} {:?} should drop and lock", rotation, shape);
+            }
+        }
+    }
+
+    #[test]
+    fn top_out() {
+        let lock_square = |config: GameConfig, y: usize| {
+            let (tx, rx) = channel();
+            let mut g = Game::with_config(tx, 0, config);
+            if y > 0 {
+                g.board.set(0, y - 1, Some(Shape::Zee.into()));
+            }
+            g.shape_controller.set_shape(Shape::Square);
+            g.shape_controller.set_position(Point::new(0, y));
+            g.start();
+            g.next(Input::Drop);
+            rx.try_iter().find_map(|o| match o {
+                Output::GameOver(reason) => Some(reason),
+                _ => None
+            })
+        };
+        assert_eq!(lock_square(GameConfig::default(), 21), Some(Some(TopOut::LockOut)), "locked wholly in the vanish zone");
+        assert_eq!(lock_square(GameConfig::default(), 19), None, "partly visible is fine by default");
+        let partial = GameConfig { partial_lock_out: true, ..GameConfig::default() };
+        assert_eq!(lock_square(partial, 19), Some(Some(TopOut::PartialLockOut)));
+        let tall = GameConfig { height: 22, ..GameConfig::default() };
+        assert_eq!(lock_square(tall, 21), None, "a taller field shows more of the board");
+
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        for x in 0..WIDTH - 1 {
+            g.board.set(x, 21, Some(Shape::Zee.into()));
+        }
+        g.shape_controller.set_shape(Shape::Square);
+        g.shape_controller.set_position(Point::new(0, 0));
+        g.start();
+        g.next(Input::Drop);
+        assert!(rx.try_iter().any(|o| o == Output::GameOver(Some(TopOut::BlockOut))), "no room for the next shape");
+        assert_eq!(g.state, GameState::Over);
+
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.start();
+        g.quit();
+        let outputs: Vec<Output> = rx.try_iter().collect();
+        assert!(outputs.contains(&Output::GameOver(None)));
+        match outputs.last() {
+            Some(Output::Finished(result)) => assert_eq!(result.outcome, Outcome::Ended),
+            o => panic!("expected the result last, got {:?}", o)
+        }
+    }
+
+    #[test]
+    fn sprint() {
+        let (tx, rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { mode: GameMode::Sprint(4), ..GameConfig::default() });
+        g.start();
+        for _ in 0..6 {
+            g.next(Input::TickGame);
+        }
+        g.next(Input::Drop);
+        for y in 0..4 {
+            g.board.fill_row(y, Some(Shape::Zee.into()));
+            g.board.set(0, y, None);
+        }
+        g.board.set(5, 4, Some(Shape::Zee.into()));
+        g.shape_controller.set_shape(Shape::Eye);
+        g.shape_controller.set_orientation(Orientation::Right);
+        g.shape_controller.set_position(Point::new(0, 10));
+        g.next(Input::Drop);
+        assert_eq!(g.state, GameState::Over, "four lines finishes the sprint");
+        let outputs: Vec<Output> = rx.try_iter().collect();
+        assert!(outputs.contains(&Output::GameOver(None)), "finishing is not losing");
+        let result = outputs.iter().find_map(|o| match o {
+            Output::Finished(result) => Some(*result),
+            _ => None
+        }).unwrap();
+        assert_eq!(result.outcome, Outcome::Completed);
+        assert_eq!((result.frames, result.time), (6, 100), "six frames at 60 a second");
+        assert_eq!((result.pieces, result.lines), (2, 4));
+        assert_eq!(GameMode::sprint(), GameMode::Sprint(40));
+    }
+
+    #[test]
+    fn ultra() {
+        let (tx, rx) = channel();
+        // a second at 10 frames a second
+        let mut g = Game::with_config(tx, 0, GameConfig { mode: GameMode::Ultra(1000), tick_rate: 10, ..GameConfig::default() });
+        g.start();
+        g.next(Input::Drop);
+        for _ in 0..9 {
+            g.next(Input::TickGame);
+        }
+        assert_eq!(g.state, GameState::Playing);
+        assert_eq!(g.time(), 900);
+        g.next(Input::TickGame);
+        assert_eq!(g.state, GameState::Over, "time is up");
+        let result = rx.try_iter().find_map(|o| match o {
+            Output::Finished(result) => Some(result),
+            _ => None
+        }).unwrap();
+        assert_eq!(result.outcome, Outcome::Completed);
+        assert_eq!((result.time, result.pieces, result.score), (1000, 1, g.score));
+        assert_eq!(GameMode::ultra(), GameMode::Ultra(120_000));
+    }
+
+    #[test]
+    fn dig() {
+        let (tx, rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { mode: GameMode::Dig(3, 2), ..GameConfig::default() });
+        g.start();
+        assert_eq!(g.board.garbage_rows(), 2, "the dig starts with its garbage in");
+        assert!((0..2).all(|y| g.board.row(y).count_ones() == 9), "every row is garbage with a hole");
+        // fills the hole in the bottom row, then drops a shape to clear it
+        let dig = |g: &mut Game, rows: usize| {
+            for y in 0..rows {
+                let hole = (!g.board.row(y) & 0b11_1111_1111).trailing_zeros() as usize;
+                g.board.set(hole, y, Some(Shape::Zee.into()));
+            }
+            g.next(Input::Drop);
+        };
+        dig(&mut g, 2);
+        assert_eq!(g.board.garbage_rows(), 1, "only one row of the dig is left to come in");
+        assert_eq!(g.state, GameState::Playing);
+        dig(&mut g, 1);
+        assert_eq!(g.state, GameState::Over, "all the garbage is cleared");
+        let result = rx.try_iter().find_map(|o| match o {
+            Output::Finished(result) => Some(result),
+            _ => None
+        }).unwrap();
+        assert_eq!((result.outcome, result.pieces, result.lines), (Outcome::Completed, 2, 3));
+        assert_eq!(GameMode::dig(), GameMode::Dig(18, 10));
+    }
+
+    #[test]
+    fn marathon() {
+        let (tx, rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { mode: GameMode::Marathon(8), start_level: 3, lines_per_level: 2, ..GameConfig::default() });
+        g.start();
+        g.board.set(5, 20, Some(Shape::Zee.into()));
+        for _ in 0..2 {
+            assert_eq!(g.state, GameState::Playing);
+            for y in 0..4 {
+                g.board.fill_row(y, Some(Shape::Zee.into()));
+                g.board.set(0, y, None);
+            }
+            g.shape_controller.set_shape(Shape::Eye);
+            g.shape_controller.set_orientation(Orientation::Right);
+            g.shape_controller.set_position(Point::new(0, 10));
+            g.next(Input::Drop);
+        }
+        assert_eq!(g.state, GameState::Over, "eight lines ends the marathon");
+        let outputs: Vec<Output> = rx.try_iter().collect();
+        assert!(outputs.contains(&Output::LevelUp(5)));
+        assert!(outputs.contains(&Output::LevelUp(7)));
+        let result = outputs.iter().find_map(|o| match o {
+            Output::Finished(result) => Some(*result),
+            _ => None
+        }).unwrap();
+        assert_eq!((result.outcome, result.lines, result.level), (Outcome::Completed, 8, 7));
+        assert_eq!(GameMode::marathon(), GameMode::Marathon(150));
+    }
+
+    #[test]
+    fn zen() {
+        let (tx, rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { mode: GameMode::Zen, ..GameConfig::default() });
+        // a full field with no lines to clear, so the first shape locks out
+        for y in 0..20 {
+            g.board.fill_row(y, Some(Shape::Zee.into()));
+            g.board.set(y % 10, y, None);
+        }
+        g.start();
+        g.next(Input::Garbage(2));
+        g.next(Input::Drop);
+        assert_eq!(g.state, GameState::Playing, "zen never ends on a top out");
+        assert!(g.board.is_empty(), "the board is cleared for the next shape");
+        assert_eq!(g.pending_garbage(), 0);
+        g.quit();
+        let outputs: Vec<Output> = rx.try_iter().collect();
+        assert!(outputs.contains(&Output::Recovered(TopOut::LockOut)));
+        assert!(!outputs.contains(&Output::GameOver(Some(TopOut::LockOut))));
+        match outputs.last() {
+            Some(Output::Finished(result)) => assert_eq!((result.outcome, result.top_outs, result.pieces), (Outcome::Ended, 1, 1)),
+            o => panic!("expected the result last, got {:?}", o)
+        }
+        assert!(GameMode::Endless.tops_out());
+        assert!(!GameMode::Zen.tops_out());
+    }
+
+    #[test]
+    fn drop_scoring() {
+        let (tx, rx) = channel();
+        // a row a second at level 1 is a 60th of a row a tick, so a 10x soft drop takes 12 ticks for two rows
+        let mut g = Game::new(tx, 0);
+        g.shape_controller.set_shape(Shape::Tee);
+        g.shape_controller.set_position(Point::new(3, 10));
+        g.start();
+        g.next(Input::Down);
+        assert_eq!(g.shape_controller.position().y, 9, "pressing down moves a row straight away");
+        assert_eq!(g.score, 1);
+        for _ in 0..12 {
+            g.next(Input::TickGame);
+        }
+        assert_eq!(g.shape_controller.position().y, 7);
+        assert_eq!(g.score, 3, "a point for every soft dropped cell");
+
+        g.next(Input::DownReleased);
+        for _ in 0..13 {
+            g.next(Input::TickGame);
+        }
+        assert_eq!(g.shape_controller.position().y, 7, "back to normal gravity");
+        assert_eq!(g.score, 3, "gravity alone scores nothing");
+
+        g.next(Input::Drop);
+        assert_eq!(g.score, 17, "two points for every hard dropped cell");
+        let scores: Vec<u32> = rx.try_iter().filter_map(|o| match o {
+            Output::ScoreUpdate(s) => Some(s),
+            _ => None
+        }).collect();
+        assert_eq!(scores, vec![1, 2, 3, 17]);
+    }
+
+    #[test]
+    fn scoring_systems() {
+        let guideline = ScoringKind::Guideline.build();
+        assert_eq!(guideline.points(&ScoreReason::Lines(1), 1), 100);
+        assert_eq!(guideline.points(&ScoreReason::Lines(4), 2), 1600);
+        assert_eq!(guideline.points(&ScoreReason::HardDrop(10), 5), 20, "drops ignore the level");
+
+        let nes = ScoringKind::Nes.build();
+        assert_eq!(nes.points(&ScoreReason::Lines(2), 0), 100);
+        assert_eq!(nes.points(&ScoreReason::Lines(4), 2), 3600);
+        assert_eq!(nes.points(&ScoreReason::HardDrop(10), 0), 0);
+
+        let simple = ScoringKind::Simple.build();
+        assert_eq!(simple.points(&ScoreReason::Lines(3), 9), 3);
+    }
+
+    #[test]
+    fn scored_output() {
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.set_level(3);
+        for y in 0..2 {
+            for x in 0..WIDTH {
+                g.board.set(x, y, Some(Shape::Square.into()));
+            }
+        }
+        g.board.set(0, 2, Some(Shape::Square.into()));
+        g.clear_lines();
+        assert_eq!(g.score, 900);
+        let outputs: Vec<Output> = rx.try_iter().collect();
+        assert_eq!(outputs[0], Output::Scored(ScoreReason::Lines(2), 900), "should say why points were given");
+        assert_eq!(outputs[1], Output::ScoreUpdate(900));
+    }
+
+    #[test]
+    fn combo_and_back_to_back() {
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        // something left over, so none of these are perfect clears
+        g.board.set(0, 20, Some(Shape::Zee.into()));
+        let clear = |g: &mut Game, lines: usize| {
+            for y in 0..lines {
+                g.board.fill_row(y, Some(Shape::Square.into()));
+            }
+            g.clear_lines();
+            (g.combo(), g.back_to_back())
+        };
+        assert_eq!(clear(&mut g, 4), (1, 1));
+        assert_eq!(g.score, 800);
+        assert_eq!(clear(&mut g, 4), (2, 2));
+        assert_eq!(g.score, 800 + 800 + 400 + 50, "back to back pays half again, plus the combo");
+        assert_eq!(clear(&mut g, 1), (3, 0), "a single breaks back to back but keeps the combo");
+        assert_eq!(g.score, 2050 + 100 + 100);
+        assert_eq!(clear(&mut g, 0), (0, 0), "a lock that clears nothing ends the combo");
+
+        let streaks: Vec<(u32, u32)> = rx.try_iter().filter_map(|o| match o {
+            Output::Combo(c, b) => Some((c, b)),
+            _ => None
+        }).collect();
+        assert_eq!(streaks, vec![(1, 1), (2, 2), (3, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn perfect_clear() {
+        let (tx, rx) = channel();
+        let mut g = Game::new(tx, 0);
+        g.board.fill_row(0, Some(Shape::Square.into()));
+        g.board.fill_row(1, Some(Shape::Square.into()));
+        g.board.set(4, 2, Some(Shape::Square.into()));
+        g.clear_lines();
+        assert!(!rx.try_iter().any(|o| matches!(o, Output::PerfectClear(_))), "a cell is left behind");
+
+        g.board.fill_row(0, Some(Shape::Square.into()));
+        let score = g.score;
+        g.clear_lines();
+        let outputs: Vec<Output> = rx.try_iter().collect();
+        assert!(outputs.contains(&Output::PerfectClear(1)));
+        assert!(outputs.contains(&Output::Scored(ScoreReason::PerfectClear(1), 800)));
+        assert_eq!(g.score - score, 100 + 50 + 800, "a single, the combo and the perfect clear bonus");
+    }
+
+    #[test]
+    fn gravity_curves() {
+        assert_eq!(GravityCurve::Guideline.gravity(1, 60), 1093, "a row a second is a fraction of a row a tick");
+        assert!(GravityCurve::Guideline.gravity(15, 60) > G, "level 15 falls more than a row a tick");
+        assert_eq!(GravityCurve::Guideline.gravity(20, 60), MAX_GRAVITY);
+        assert_eq!(GravityCurve::Guideline.gravity(1, 2), G / 2, "slower ticks fall further each");
+        assert_eq!(GravityCurve::Nes.gravity(0, 60), 1366);
+        assert_eq!(GravityCurve::Nes.gravity(29, 60), G);
+        // the fractions are fine enough to keep the frame counts
+        for (curve, level, frames) in [(GravityCurve::Guideline, 1, 60), (GravityCurve::Nes, 0, 48), (GravityCurve::Nes, 9, 6)].iter() {
+            let g = curve.gravity(*level, 60);
+            for rows in 1..=10 {
+                assert_eq!((rows * frames * g) / G, rows, "{:?} level {} should fall a row every {} frames", curve, level, frames);
+            }
+        }
+        let table = GravityCurve::Table(vec![48.0, 2.0, 0.05]);
+        assert_eq!(table.gravity(0, 60), 1366);
+        assert_eq!(table.gravity(1, 60), G / 2);
+        assert_eq!(table.gravity(2, 60), MAX_GRAVITY, "a twentieth of a frame a row is 20G");
+        assert_eq!(table.gravity(9, 60), MAX_GRAVITY, "the last entry holds for higher levels");
+    }
+
+    #[test]
+    fn twenty_g() {
+        let (tx, _rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { start_level: 20, ..GameConfig::default() });
+        g.start();
+        g.next(Input::TickGame);
+        assert!(g.shape_collides(), "20G should land the shape on its first tick");
+    }
+
+    #[test]
+    fn level_up() {
+        let (tx, rx) = channel();
+        let mut g = Game::with_config(tx, 0, GameConfig { lines_per_level: 2, ..GameConfig::default() });
+        g.board.fill_row(0, Some(Shape::Square.into()));
+        g.clear_lines();
+        assert_eq!(g.level(), 1);
+        g.board.fill_row(0, Some(Shape::Square.into()));
+        g.clear_lines();
+        assert_eq!(g.level(), 2, "two lines should take the game up a level");
+        assert!(rx.try_iter().any(|o| o == Output::LevelUp(2)));
+        assert_eq!(g.gravity(), GravityCurve::Guideline.gravity(2, 60), "gravity follows the level");
+    }
+
     #[test]
     fn clear_lines() {
+        let mut rng = StdRng::seed_from_u64(0);
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         let config = vec![
             vec![None, None, None, None, None, None, None,  None, None, None],
             vec![None, None, None, None, None, None, None,  None, None, None],
             vec![None, None, None, None, None, None, None,  None, None, None], 
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
         ];
         g.board.setup(config, Point::new(0,0), false);
         g.shape_controller.set_shape(Shape::Eye);
         g.shape_controller.set_orientation(Orientation::Up);
         g.shape_controller.set_position(Point::new(1,0));
-        g.board.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.occupy(&mut g.board);
         g.start();
      
         assert!(g.score == 0, "line count should be zero");
@@ -894,25 +2312,26 @@ mod tests {
         assert!(g.score == 4, "line count should be four");
         for y in 0..HEIGHT { 
             for x in 0..WIDTH { 
-                assert!(g.board.0[y][x] == None, "board should be clear");
+                assert!(g.board.get(x, y) == None, "board should be clear");
             }
         }
     }
 
     #[test]
     fn drop() {
+        let mut rng = StdRng::seed_from_u64(0);
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         let mut b = Board::new();
         let config = vec![
             vec![None, None, None, None, None, None, None,  None, None, None],
             vec![None, None, None, None, None, None, None,  None, None, None],
             vec![None, None, None, None, None, None, None,  None, None, None], 
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
         ];
         b.setup(config, Point::new(0,0), false);
         g.shape_controller.set_shape(Shape::El);
@@ -942,7 +2361,7 @@ mod tests {
 
         let (tx, _rx) = channel();
 
-        let mut g = Game::new(tx);
+        let mut g = legacy_game(tx);
         let mut b = Board::new();
         let config = vec![
             vec![None, None, None, None, None, None, None,  None, None, None],
@@ -959,26 +2378,23 @@ mod tests {
         g.shape_controller.set_position(Point::new(2,16));
         g.start();
         g.shape_controller().drop(&b);        
-        b.occupy(
-            &g.shape_controller.shape().to_mat(g.shape_controller.orientation()),
-            g.shape_controller.position()
-        );
+        g.shape_controller.occupy(&mut b);
         println!("{}", b.report());
         assert!(g.shape_controller().position().x == 2, "x should be 2");
         assert!(g.shape_controller().position().y == 0, "y should be 0 but was {}", g.shape_controller().position().y);
-        assert!(b.0[0][3] != None, "Spot 3, 0 should be filled!");
-        assert!(b.0[0][3].unwrap() == Shape::El, "The El shape should occupy x = 3, y = 0");
+        assert!(b.get(3, 0) != None, "Spot 3, 0 should be filled!");
+        assert!(b.get(3, 0).unwrap() == Cell::Shape(Shape::El), "The El shape should occupy x = 3, y = 0");
     }
 
 
     #[test]
     fn play() {
-        let g = crate::game();
+        let g = crate::game(0, quick_config());
         let (_h, _rx, txi) = g.tuple();
         txi.lock().unwrap().send(Input::StartGame).unwrap();
         let txclock = txi.clone();
         thread::spawn(move || {
-            while !txclock.lock().unwrap().send(Input::TickGame).is_err() {
+            while txclock.lock().unwrap().send(Input::TickGame).is_ok() {
                 thread::sleep(time::Duration::from_millis(1));
             }
         });
@@ -988,7 +2404,7 @@ mod tests {
 
     #[test]
     fn read_events() {
-        let g = crate::game();
+        let g = crate::game(0, quick_config());
         let (_h, rx, txi) = g.tuple();
         txi.lock().unwrap().send(Input::StartGame).unwrap();
         match rx.lock().unwrap().recv() {
@@ -1001,7 +2417,7 @@ mod tests {
         };
         let txclock = txi.clone();
         thread::spawn(move || {
-            while !txclock.lock().unwrap().send(Input::TickGame).is_err() {
+            while txclock.lock().unwrap().send(Input::TickGame).is_ok() {
                 thread::sleep(time::Duration::from_millis(1));
             }
         });
@@ -1030,14 +2446,15 @@ mod tests {
         let txclock = tx.clone();
         let txctrl = tx.clone();        
         thread::spawn(move || {
-            while !txclock.send(Input::TickGame).is_err() {
+            while txclock.send(Input::TickGame).is_ok() {
                 thread::sleep(time::Duration::from_millis(10));
             }
         });
 
         if !no_input {
             thread::spawn(move || {
-                while !txctrl.send(Input::rand_control()).is_err() {
+                let mut rng = StdRng::seed_from_u64(0);
+                while txctrl.send(Input::rand_control(&mut rng)).is_ok() {
                     thread::sleep(time::Duration::from_millis(70));
                 }
             });
@@ -1054,7 +2471,7 @@ mod tests {
 
     #[test]
     fn write_events() {
-        let g = crate::game();        
+        let g = crate::game(0, quick_config());        
         let mut v = Vec::new();
 
         self_play(&g.output_receiver.lock().unwrap(), &g.input_sender.lock().unwrap(), false, &mut v);
@@ -1064,19 +2481,193 @@ mod tests {
         g.join_handle.join().unwrap();
     }
 
+    #[test]
+    fn seeded_games_replay() {
+        // zen so topping out doesn't cut the run short
+        let play_out = |seed: u64| {
+            let (tx, rx) = channel();
+            let mut g = Game::with_config(tx, seed, GameConfig { mode: GameMode::Zen, ..GameConfig::default() });
+            let mut rng = StdRng::seed_from_u64(7);
+            g.start();
+            for i in 0..3000 {
+                let input = match i % 30 {
+                    0 => Input::Drop,
+                    10 => Input::Hold,
+                    20 => Input::Garbage(1),
+                    n if n % 3 == 0 => Input::rand_control(&mut rng),
+                    _ => Input::TickGame
+                };
+                g.next(input);
+            }
+            rx.try_iter().collect::<Vec<Output>>()
+        };
+        let first = play_out(42);
+        let second = play_out(42);
+        let locks = first.iter().filter(|o| matches!(o, Output::ShapeLocked(_, _))).count();
+        let garbage = first.iter().filter(|o| matches!(o, Output::GarbageReceived(_))).count();
+        assert!(locks >= 80, "expect the run to lock plenty of shapes, locked {}", locks);
+        assert!(garbage >= 50, "expect garbage to rise during the run, rose {} times", garbage);
+        assert!(first == second, "same seed and same inputs should give the same outputs");
+        assert!(first != play_out(43), "a different seed should give different outputs");
+    }
+
+    #[test]
+    fn bag_deals_every_shape() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut bag = RandomizerKind::Bag.build();
+        for _ in 0..10 {
+            let seen: Vec<Shape> = (0..7).map(|_| bag.next(&mut rng)).collect();
+            for shape in Shape::ALL.iter() {
+                assert_eq!(seen.iter().filter(|s| *s == shape).count(), 1, "every bag should hold one {:?}", shape);
+            }
+        }
+    }
+
+    // an rng that plays back a script of draws, so the tests can say
+    // exactly what each roll comes up as
+    struct Script(VecDeque<u32>);
+
+    impl rand::RngCore for Script {
+        fn next_u32(&mut self) -> u32 {
+            return self.0.pop_front().expect("rolled more often than the script allows");
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            return (self.next_u32() as u64) << 32;
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(4) {
+                let bytes = self.next_u32().to_le_bytes();
+                chunk.copy_from_slice(&bytes[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            return Ok(());
+        }
+    }
+
+    // the draw that makes gen_range(0, n) come up k
+    fn roll(k: u64, n: u64) -> u32 {
+        return (k << 32).div_ceil(n) as u32;
+    }
+
+    fn script(draws: &[(u64, u64)]) -> Script {
+        return Script(draws.iter().map(|(k, n)| roll(*k, *n)).collect());
+    }
+
+    #[test]
+    fn nes_rerolls() {
+        let mut nes = RandomizerKind::Nes.build();
+        let mut rng = script(&[(0, 8)]);
+        assert_eq!(nes.next(&mut rng), Shape::Eye);
+        let mut rng = script(&[(0, 8), (3, 7)]);
+        assert_eq!(nes.next(&mut rng), Shape::Square, "a repeat should be rolled again");
+        assert!(rng.0.is_empty());
+        let mut rng = script(&[(7, 8), (3, 7)]);
+        assert_eq!(nes.next(&mut rng), Shape::Square, "the dummy side rolls again, and the second roll may repeat");
+        assert!(rng.0.is_empty());
+        let mut rng = script(&[(2, 8)]);
+        assert_eq!(nes.next(&mut rng), Shape::ElInv, "anything new stands");
+    }
+
+    #[test]
+    fn tgm_rerolls() {
+        let mut tgm = Tgm::new(4);
+        let mut rng = script(&[(3, 4)]);
+        assert_eq!(tgm.next(&mut rng), Shape::Tee);
+        // the history is now S Z S T
+        let mut rng = script(&[(6, 7), (4, 7), (0, 7)]);
+        assert_eq!(tgm.next(&mut rng), Shape::Eye, "shapes in the history should be rolled again");
+        assert!(rng.0.is_empty());
+        // the history is now Z S T I
+        let mut rng = script(&[(6, 7), (0, 7), (4, 7), (5, 7)]);
+        assert_eq!(tgm.next(&mut rng), Shape::ZeeInv, "after four draws the last one stands");
+        assert!(rng.0.is_empty());
+    }
+
+    #[test]
+    fn tgm_first_shape() {
+        for seed in 0..50 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let first = RandomizerKind::Tgm.build().next(&mut rng);
+            assert!(first != Shape::Square && first != Shape::Zee && first != Shape::ZeeInv, "tgm should never open with {:?}", first);
+        }
+    }
+
+    #[test]
+    fn nes_shapes() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut nes = RandomizerKind::Nes.build();
+        let shapes: Vec<Shape> = (0..700).map(|_| nes.next(&mut rng)).collect();
+        for shape in Shape::ALL.iter() {
+            assert!(shapes.contains(shape), "nes should eventually deal a {:?}", shape);
+        }
+    }
+
+    #[test]
+    fn game_uses_configured_randomizer() {
+        let (tx, rx) = channel();
+        let config = GameConfig { randomizer: RandomizerKind::Bag, preview: 1, ..GameConfig::default() };
+        let mut g = Game::with_config(tx, 11, config);
+        g.start();
+        let mut dealt = vec![g.shape_controller.shape(), g.next_shapes[0]];
+        while dealt.len() < 7 {
+            g.next(Input::Drop);
+            dealt.push(g.next_shapes[0]);
+        }
+        for shape in Shape::ALL.iter() {
+            assert!(dealt.contains(shape), "the first bag should hold a {:?}", shape);
+        }
+        assert!(rx.try_iter().count() > 0);
+    }
+
+    #[test]
+    fn preview_queue() {
+        let (tx, rx) = channel();
+        let config = GameConfig { preview: 3, ..GameConfig::default() };
+        let mut g = Game::with_config(tx, 5, config);
+        g.start();
+        let queue: Vec<Shape> = g.next_shapes.iter().cloned().collect();
+        assert_eq!(queue.len(), 3, "preview should hold three shapes");
+        assert!(rx.try_iter().any(|o| o == Output::NextShapes(queue.clone())), "start should report the whole queue");
+
+        let current = g.shape_controller.shape();
+        g.next(Input::Hold);
+        assert_eq!(g.hold_shape, Some(current));
+        assert_eq!(g.shape_controller.shape(), queue[0], "hold should bring in the head of the queue");
+        assert_eq!(g.next_shapes[0], queue[1]);
+        assert_eq!(g.next_shapes[1], queue[2]);
+        assert_eq!(g.next_shapes.len(), 3);
+        let reported: Vec<Shape> = g.next_shapes.iter().cloned().collect();
+        assert!(rx.try_iter().any(|o| o == Output::NextShapes(reported.clone())), "hold should report the new queue");
+
+        g.next(Input::Drop);
+        assert_eq!(g.shape_controller.shape(), queue[1], "a lock should bring in the head of the queue");
+        assert_eq!(g.next_shapes.len(), 3);
+    }
+
+    #[test]
+    fn preview_bounds() {
+        let (tx, _rx) = channel();
+        let g = Game::with_config(tx.clone(), 5, GameConfig { preview: 0, ..GameConfig::default() });
+        assert_eq!(g.next_shapes.len(), 1);
+        let g = Game::with_config(tx, 5, GameConfig { preview: 12, ..GameConfig::default() });
+        assert_eq!(g.next_shapes.len(), 7);
+    }
+
     #[test]
     fn trasheroonie() {
+        let mut rng = StdRng::seed_from_u64(0);
         let (_tx, _rx) = channel();
-        let g = Game::new(_tx);
-        let mut b = g.board;
+        let g = Game::new(_tx, 0);
+        let mut b = g.board.clone();
         let mut trash_count = 0;
-        b.trash(10);
-        for x in b.0.iter() {
-            for y in x.iter() {
-                if *y != None {
-                    trash_count += 1;
-                }
-            }
+        b.trash(10, &mut rng);
+        for y in 0..b.height() {
+            trash_count += b.row(y).count_ones();
         }
         assert_eq!(trash_count, 10, "should be 10 trash, but there was {} trash", trash_count);
 
@@ -1085,7 +2676,7 @@ mod tests {
     #[test]
     fn holds() {
         println!("running hold test");
-        let g = crate::game();
+        let g = crate::game(0, quick_config());
         let (_h, rx, tx) = g.tuple();
         tx.lock().unwrap().send(Input::StartGame).unwrap();        
         match rx.lock().unwrap().recv() {
@@ -1100,7 +2691,7 @@ mod tests {
         let txclock = tx.clone();
 
         thread::spawn(move || {
-            while !txclock.lock().unwrap().send(Input::TickGame).is_err() {
+            while txclock.lock().unwrap().send(Input::TickGame).is_ok() {
                 thread::sleep(time::Duration::from_millis(1));
             }
         });
@@ -1124,7 +2715,7 @@ mod tests {
                     assert!(false, "well, no way. we got an error response and that should be covered by another test.");
                 }
             }
-            counter = counter + 1;
+            counter += 1;
             assert!(counter < 10, "we expected a response about holding the shape and did not get one :(");
         }
         
@@ -1133,7 +2724,7 @@ mod tests {
  
     #[test]
     fn shape_lock() {
-        let g = crate::game();
+        let g = crate::game(0, quick_config());
         let (_h, rx, tx) = g.tuple();
         let mut v = Vec::new();
 
@@ -1156,20 +2747,21 @@ mod tests {
 
     #[test]
     fn lines_completed() {
+        let mut rng = StdRng::seed_from_u64(0);
 
         let (txo, rxo) = channel();
         let (txi, rxi) = channel();
 
-        let mut g = Game::new(txo);
+        let mut g = Game::with_config(txo, 0, GameConfig { rotation: RotationKind::Legacy, ..quick_config() });
         let mut b = Board::new();
         let config = vec![
             vec![None, None, None, None, None, None, None,  None, None, None],
             vec![None, None, None, None, None, None, None,  None, None, None],
             vec![None, None, None, None, None, None, None,  None, None, None], 
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
-            vec![Some(Shape::random()),  None, Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random()),   Some(Shape::random()),  Some(Shape::random()),  Some(Shape::random())],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
+            vec![Some(Shape::random(&mut rng)),  None, Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),   Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng)),  Some(Shape::random(&mut rng))],
         ];
         let mut log = Vec::new();
 
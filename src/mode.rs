@@ -0,0 +1,60 @@
+use crate::event::TopOut;
+
+/// What a game is played for.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameMode {
+    Endless, // until the player tops out or the game is ended
+    Sprint(u32), // clear this many lines as fast as possible
+    Ultra(u64), // score as much as possible in this many ms of game time
+    Marathon(u32), // level up through GameConfig::lines_per_level until this many lines
+    Dig(u32, u32), // clear this many rows of garbage, kept topped up to this many on the board
+    Zen, // topping out clears the board and play goes on
+}
+
+impl GameMode {
+    /// The usual 40 line sprint.
+    pub fn sprint() -> GameMode {
+        return GameMode::Sprint(40);
+    }
+
+    /// The usual two minute ultra.
+    pub fn ultra() -> GameMode {
+        return GameMode::Ultra(120_000);
+    }
+
+    /// The usual 150 line marathon, 15 levels at 10 lines a level.
+    pub fn marathon() -> GameMode {
+        return GameMode::Marathon(150);
+    }
+
+    /// An 18 line cheese race, ten rows of garbage at a time.
+    pub fn dig() -> GameMode {
+        return GameMode::Dig(18, 10);
+    }
+
+    /// Whether topping out ends the game.
+    pub fn tops_out(&self) -> bool {
+        return *self != GameMode::Zen;
+    }
+}
+
+/// How a game came to an end.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Outcome {
+    Completed, // the mode's goal was reached
+    ToppedOut(TopOut),
+    Ended, // by Input::EndGame
+}
+
+/// The final tally, sent once a game is over.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GameResult {
+    pub outcome: Outcome,
+    pub time: u64, // ms of game time, from the frames played and the tick rate
+    pub frames: u64,
+    pub pieces: u32, // shapes locked
+    pub top_outs: u32, // times the board was cleared instead of ending the game
+    pub lines: u32,
+    pub score: u32,
+    pub level: u8,
+}
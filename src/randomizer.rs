@@ -0,0 +1,131 @@
+use crate::shape::Shape;
+use rand::RngCore;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::collections::VecDeque;
+
+/// Picks the sequence of pieces a game deals out.  The rng belongs to the
+/// game, so a randomizer only keeps whatever history its rules need.
+pub trait Randomizer: Send {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RandomizerKind {
+    Uniform, Bag, Nes, Tgm
+}
+
+impl RandomizerKind {
+    pub fn build(&self) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerKind::Uniform => Box::new(Uniform),
+            RandomizerKind::Bag => Box::new(Bag::new()),
+            RandomizerKind::Nes => Box::new(Nes::new()),
+            RandomizerKind::Tgm => Box::new(Tgm::new(6)),
+        }
+    }
+}
+
+/// Every piece is an independent, uniform draw.
+pub struct Uniform;
+
+impl Randomizer for Uniform {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        Shape::random(rng)
+    }
+}
+
+/// Deals all seven pieces in a shuffled order before refilling the bag.
+pub struct Bag {
+    bag: Vec<Shape>
+}
+
+impl Bag {
+    pub fn new() -> Bag {
+        Bag { bag: Vec::new() }
+    }
+}
+
+impl Default for Bag {
+    fn default() -> Bag {
+        Bag::new()
+    }
+}
+
+impl Randomizer for Bag {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        if self.bag.is_empty() {
+            self.bag.extend_from_slice(&Shape::ALL);
+            self.bag.shuffle(rng);
+        }
+        return self.bag.pop().unwrap();
+    }
+}
+
+/// The NES draw: roll eight sides, and if that lands on the dummy side or
+/// repeats the last piece, roll once more over the seven real pieces.
+pub struct Nes {
+    last: Option<Shape>
+}
+
+impl Nes {
+    pub fn new() -> Nes {
+        Nes { last: None }
+    }
+}
+
+impl Default for Nes {
+    fn default() -> Nes {
+        Nes::new()
+    }
+}
+
+impl Randomizer for Nes {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        let roll = rng.gen_range(0, 8);
+        let mut shape = if roll < 7 { Some(Shape::ALL[roll]) } else { None };
+        if shape == None || shape == self.last {
+            shape = Some(Shape::random(rng));
+        }
+        self.last = shape;
+        return shape.unwrap();
+    }
+}
+
+/// TGM style history: draw up to `rolls` times in all while the draw is one
+/// of the last four pieces, keeping the last draw if every one was.  The
+/// history starts out full of S and Z pieces and the first piece is never
+/// an S, Z or O.
+pub struct Tgm {
+    history: VecDeque<Shape>,
+    rolls: u8,
+    first: bool
+}
+
+impl Tgm {
+    pub fn new(rolls: u8) -> Tgm {
+        let history = vec![Shape::Zee, Shape::ZeeInv, Shape::Zee, Shape::ZeeInv];
+        Tgm { history: history.into_iter().collect(), rolls: rolls, first: true }
+    }
+}
+
+impl Randomizer for Tgm {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        let shape = if self.first {
+            self.first = false;
+            *[Shape::Eye, Shape::El, Shape::ElInv, Shape::Tee].choose(rng).unwrap()
+        } else {
+            let mut shape = Shape::random(rng);
+            for _ in 1..self.rolls {
+                if !self.history.contains(&shape) {
+                    break;
+                }
+                shape = Shape::random(rng);
+            }
+            shape
+        };
+        self.history.pop_front();
+        self.history.push_back(shape);
+        return shape;
+    }
+}
@@ -0,0 +1,589 @@
+use crate::shape::{Shape, ShapeMat, ShapeRows, Orientation, Point};
+use crate::board::Board;
+use crate::{WIDTH, HEIGHT};
+use std::sync::Arc;
+
+/// A rotation system owns how shapes look in each orientation, where they
+/// spawn and how they kick when a rotation is blocked.
+///
+/// Shapes turn inside a rotation box.  `cells` lists the four cells of a
+/// shape inside that box as (x, y) with y = 0 at the bottom.  The game only
+/// tracks the bottom left corner of the packed shape matrix, so `offset`
+/// is used to step out to the box and back in again when rotating.
+pub trait RotationSystem: Send + Sync {
+    fn cells(&self, s: Shape, o: Orientation) -> [(usize, usize); 4];
+
+    /// The bottom left corner of the rotation box for a freshly spawned
+    /// shape, on a board `width` wide with `visible` rows below the vanish zone.
+    fn spawn_box(&self, s: Shape, width: usize, visible: usize) -> (usize, usize);
+
+    /// (x, y) offsets to try in order, y up.
+    fn kicks(&self, _s: Shape, _from: Orientation, _to: Orientation) -> Vec<(isize, isize)> {
+        vec![(0, 0)]
+    }
+
+    fn offset(&self, s: Shape, o: Orientation) -> (usize, usize) {
+        let cells = self.cells(s, o);
+        let x = cells.iter().map(|c| c.0).min().unwrap();
+        let y = cells.iter().map(|c| c.1).min().unwrap();
+        (x, y)
+    }
+
+    fn mat(&self, s: Shape, o: Orientation) -> ShapeMat {
+        let (ox, oy) = self.offset(s, o);
+        let mut m = [[None; 4]; 4];
+        for (x, y) in self.cells(s, o).iter() {
+            m[3 - (y - oy)][x - ox] = Some(s);
+        }
+        m
+    }
+
+    /// The shape as row masks, packed into the bottom left like `mat`.
+    fn rows(&self, s: Shape, o: Orientation) -> ShapeRows {
+        let (ox, oy) = self.offset(s, o);
+        let mut rows = [0; 4];
+        for (x, y) in self.cells(s, o).iter() {
+            rows[y - oy] |= 1 << (x - ox);
+        }
+        rows
+    }
+
+    fn spawn(&self, s: Shape, width: usize, visible: usize) -> Point {
+        let (bx, by) = self.spawn_box(s, width, visible);
+        let (ox, oy) = self.offset(s, Orientation::Up);
+        Point::new(bx + ox, by + oy)
+    }
+
+    /// Rows above the visible field needed for every shape to spawn and
+    /// turn in place without leaving the board.
+    fn vanish_zone(&self) -> usize {
+        let mut top = 0;
+        for s in Shape::ALL.iter() {
+            let (_, by) = self.spawn_box(*s, WIDTH, HEIGHT);
+            for o in ORIENTATIONS.iter() {
+                let height = self.cells(*s, *o).iter().map(|c| c.1).max().unwrap() + 1;
+                top = top.max(by + height);
+            }
+        }
+        top.saturating_sub(HEIGHT)
+    }
+
+    /// Where the shape at `p` ends up after turning from `from` to `to`, or
+    /// `None` if it cannot turn at all.
+    fn rotate(&self, s: Shape, from: Orientation, to: Orientation, p: &Point, b: &Board) -> Option<Point> {
+        kick(self, s, from, to, p, b, &self.kicks(s, from, to))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RotationKind {
+    Srs, Ars, Nes, Legacy
+}
+
+impl RotationKind {
+    pub fn build(&self) -> Arc<dyn RotationSystem> {
+        match self {
+            RotationKind::Srs => Arc::new(Masked::new(Srs)),
+            RotationKind::Ars => Arc::new(Masked::new(Ars)),
+            RotationKind::Nes => Arc::new(Masked::new(Nes)),
+            RotationKind::Legacy => Arc::new(Masked::new(Legacy)),
+        }
+    }
+}
+
+/// Wraps a rotation system with its row masks worked out up front, since
+/// the game asks for them on every collision check.
+pub struct Masked<R: RotationSystem> {
+    system: R,
+    rows: [[ShapeRows; 4]; 7] // by shape, then orientation
+}
+
+const ORIENTATIONS: [Orientation; 4] = [Orientation::Up, Orientation::Down, Orientation::Left, Orientation::Right];
+
+impl<R: RotationSystem> Masked<R> {
+    pub fn new(system: R) -> Masked<R> {
+        let mut rows = [[[0; 4]; 4]; 7];
+        for s in Shape::ALL.iter() {
+            for o in ORIENTATIONS.iter() {
+                rows[*s as usize][*o as usize] = system.rows(*s, *o);
+            }
+        }
+        return Masked { system: system, rows: rows };
+    }
+}
+
+impl<R: RotationSystem> RotationSystem for Masked<R> {
+    fn cells(&self, s: Shape, o: Orientation) -> [(usize, usize); 4] {
+        self.system.cells(s, o)
+    }
+
+    fn spawn_box(&self, s: Shape, width: usize, visible: usize) -> (usize, usize) {
+        self.system.spawn_box(s, width, visible)
+    }
+
+    fn kicks(&self, s: Shape, from: Orientation, to: Orientation) -> Vec<(isize, isize)> {
+        self.system.kicks(s, from, to)
+    }
+
+    fn offset(&self, s: Shape, o: Orientation) -> (usize, usize) {
+        self.system.offset(s, o)
+    }
+
+    fn mat(&self, s: Shape, o: Orientation) -> ShapeMat {
+        self.system.mat(s, o)
+    }
+
+    fn rows(&self, s: Shape, o: Orientation) -> ShapeRows {
+        self.rows[s as usize][o as usize]
+    }
+
+    fn spawn(&self, s: Shape, width: usize, visible: usize) -> Point {
+        self.system.spawn(s, width, visible)
+    }
+
+    fn rotate(&self, s: Shape, from: Orientation, to: Orientation, p: &Point, b: &Board) -> Option<Point> {
+        self.system.rotate(s, from, to, p, b)
+    }
+}
+
+// tries each kick from the rotation box the shape is in now
+pub fn kick<R: RotationSystem + ?Sized>(r: &R, s: Shape, from: Orientation, to: Orientation, p: &Point, b: &Board, kicks: &[(isize, isize)]) -> Option<Point> {
+    let (from_x, from_y) = r.offset(s, from);
+    let (to_x, to_y) = r.offset(s, to);
+    // the corner of the rotation box, which may hang off the board
+    let box_x = p.x as isize - from_x as isize;
+    let box_y = p.y as isize - from_y as isize;
+    let m = r.rows(s, to);
+
+    for (kx, ky) in kicks.iter() {
+        let x = box_x + kx + to_x as isize;
+        let y = box_y + ky + to_y as isize;
+        if x < 0 || y < 0 {
+            continue;
+        }
+        let candidate = Point::new(x as usize, y as usize);
+        if !b.collides(&m, &candidate) {
+            return Some(candidate);
+        }
+    }
+    return None;
+}
+
+/// The Super Rotation System used by guideline games.
+pub struct Srs;
+
+impl RotationSystem for Srs {
+    // 3x3 boxes, 4x4 for the Eye
+    fn cells(&self, s: Shape, o: Orientation) -> [(usize, usize); 4] {
+        match s {
+            Shape::Tee => match o {
+                Orientation::Up => [(1, 2), (0, 1), (1, 1), (2, 1)],
+                Orientation::Right => [(1, 2), (1, 1), (2, 1), (1, 0)],
+                Orientation::Down => [(0, 1), (1, 1), (2, 1), (1, 0)],
+                Orientation::Left => [(1, 2), (0, 1), (1, 1), (1, 0)],
+            },
+            Shape::ElInv => match o {
+                Orientation::Up => [(0, 2), (0, 1), (1, 1), (2, 1)],
+                Orientation::Right => [(1, 2), (2, 2), (1, 1), (1, 0)],
+                Orientation::Down => [(0, 1), (1, 1), (2, 1), (2, 0)],
+                Orientation::Left => [(1, 2), (1, 1), (0, 0), (1, 0)],
+            },
+            Shape::El => match o {
+                Orientation::Up => [(2, 2), (0, 1), (1, 1), (2, 1)],
+                Orientation::Right => [(1, 2), (1, 1), (1, 0), (2, 0)],
+                Orientation::Down => [(0, 1), (1, 1), (2, 1), (0, 0)],
+                Orientation::Left => [(0, 2), (1, 2), (1, 1), (1, 0)],
+            },
+            Shape::ZeeInv => match o {
+                Orientation::Up => [(1, 2), (2, 2), (0, 1), (1, 1)],
+                Orientation::Right => [(1, 2), (1, 1), (2, 1), (2, 0)],
+                Orientation::Down => [(1, 1), (2, 1), (0, 0), (1, 0)],
+                Orientation::Left => [(0, 2), (0, 1), (1, 1), (1, 0)],
+            },
+            Shape::Zee => match o {
+                Orientation::Up => [(0, 2), (1, 2), (1, 1), (2, 1)],
+                Orientation::Right => [(2, 2), (1, 1), (2, 1), (1, 0)],
+                Orientation::Down => [(0, 1), (1, 1), (1, 0), (2, 0)],
+                Orientation::Left => [(1, 2), (0, 1), (1, 1), (0, 0)],
+            },
+            Shape::Eye => match o {
+                Orientation::Up => [(0, 2), (1, 2), (2, 2), (3, 2)],
+                Orientation::Right => [(2, 3), (2, 2), (2, 1), (2, 0)],
+                Orientation::Down => [(0, 1), (1, 1), (2, 1), (3, 1)],
+                Orientation::Left => [(1, 3), (1, 2), (1, 1), (1, 0)],
+            },
+            Shape::Square => [(1, 2), (2, 2), (1, 1), (2, 1)],
+        }
+    }
+
+    // every box spawns with its left edge in column 3, the shape resting on row 21
+    fn spawn_box(&self, s: Shape, width: usize, visible: usize) -> (usize, usize) {
+        (width.saturating_sub(3) / 2, visible + 1 - self.offset(s, Orientation::Up).1)
+    }
+
+    // taken from the guideline tables, with the common 180 table on top
+    fn kicks(&self, s: Shape, from: Orientation, to: Orientation) -> Vec<(isize, isize)> {
+        use Orientation::*;
+        match s {
+            Shape::Square => vec![(0, 0)],
+            _ if to == from.cw().cw() => match from {
+                Up => vec![(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],
+                Down => vec![(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)],
+                Right => vec![(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],
+                Left => vec![(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],
+            },
+            Shape::Eye => match (from, to) {
+                (Up, Right) => vec![(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+                (Right, Up) => vec![(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+                (Right, Down) => vec![(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+                (Down, Right) => vec![(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+                (Down, Left) => vec![(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+                (Left, Down) => vec![(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+                (Left, Up) => vec![(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+                (Up, Left) => vec![(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+                _ => vec![(0, 0)],
+            },
+            _ => match (from, to) {
+                (Up, Right) => vec![(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+                (Right, Up) => vec![(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+                (Right, Down) => vec![(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+                (Down, Right) => vec![(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+                (Down, Left) => vec![(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+                (Left, Down) => vec![(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+                (Left, Up) => vec![(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+                (Up, Left) => vec![(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+                _ => vec![(0, 0)],
+            }
+        }
+    }
+}
+
+/// The Arika rotation system from TGM.  Shapes spawn flat side up, rest on
+/// the bottom of their box, and kick one cell right then one cell left.
+/// The Eye never kicks.
+pub struct Ars;
+
+impl RotationSystem for Ars {
+    fn cells(&self, s: Shape, o: Orientation) -> [(usize, usize); 4] {
+        match s {
+            Shape::Tee => match o {
+                Orientation::Up => [(0, 1), (1, 1), (2, 1), (1, 0)],
+                Orientation::Right => [(1, 2), (0, 1), (1, 1), (1, 0)],
+                Orientation::Down => [(1, 1), (0, 0), (1, 0), (2, 0)],
+                Orientation::Left => [(1, 2), (1, 1), (2, 1), (1, 0)],
+            },
+            Shape::El => match o {
+                Orientation::Up => [(0, 1), (1, 1), (2, 1), (0, 0)],
+                Orientation::Right => [(0, 2), (1, 2), (1, 1), (1, 0)],
+                Orientation::Down => [(2, 1), (0, 0), (1, 0), (2, 0)],
+                Orientation::Left => [(1, 2), (1, 1), (1, 0), (2, 0)],
+            },
+            Shape::ElInv => match o {
+                Orientation::Up => [(0, 1), (1, 1), (2, 1), (2, 0)],
+                Orientation::Right => [(1, 2), (1, 1), (0, 0), (1, 0)],
+                Orientation::Down => [(0, 1), (0, 0), (1, 0), (2, 0)],
+                Orientation::Left => [(1, 2), (2, 2), (1, 1), (1, 0)],
+            },
+            Shape::ZeeInv => match o {
+                Orientation::Up | Orientation::Down => [(1, 1), (2, 1), (0, 0), (1, 0)],
+                Orientation::Right | Orientation::Left => [(0, 2), (0, 1), (1, 1), (1, 0)],
+            },
+            Shape::Zee => match o {
+                Orientation::Up | Orientation::Down => [(0, 1), (1, 1), (1, 0), (2, 0)],
+                Orientation::Right | Orientation::Left => [(2, 2), (1, 1), (2, 1), (1, 0)],
+            },
+            Shape::Eye => match o {
+                Orientation::Up | Orientation::Down => [(0, 2), (1, 2), (2, 2), (3, 2)],
+                Orientation::Right | Orientation::Left => [(2, 3), (2, 2), (2, 1), (2, 0)],
+            },
+            Shape::Square => [(1, 1), (2, 1), (1, 0), (2, 0)],
+        }
+    }
+
+    fn spawn_box(&self, s: Shape, width: usize, visible: usize) -> (usize, usize) {
+        (width.saturating_sub(3) / 2, visible + 1 - self.offset(s, Orientation::Up).1)
+    }
+
+    fn kicks(&self, s: Shape, _from: Orientation, _to: Orientation) -> Vec<(isize, isize)> {
+        match s {
+            Shape::Eye | Shape::Square => vec![(0, 0)],
+            _ => vec![(0, 0), (1, 0), (-1, 0)],
+        }
+    }
+
+    fn rotate(&self, s: Shape, from: Orientation, to: Orientation, p: &Point, b: &Board) -> Option<Point> {
+        let kicks = self.kicks(s, from, to);
+        if let Some(p) = kick(self, s, from, to, p, b, &kicks[..1]) {
+            return Some(p);
+        }
+        // L, J and T refuse to kick when the first blocked cell, reading
+        // the box top to bottom and left to right, is in the middle column
+        if s == Shape::El || s == Shape::ElInv || s == Shape::Tee {
+            let (from_x, from_y) = self.offset(s, from);
+            let box_x = p.x as isize - from_x as isize;
+            let box_y = p.y as isize - from_y as isize;
+            let mut cells = self.cells(s, to);
+            cells.sort_by(|a, c| c.1.cmp(&a.1).then(a.0.cmp(&c.0)));
+            for (x, y) in cells.iter() {
+                if b.blocked(box_x + *x as isize, box_y + *y as isize) {
+                    if *x == 1 {
+                        return None;
+                    }
+                    break;
+                }
+            }
+        }
+        kick(self, s, from, to, p, b, &kicks[1..])
+    }
+}
+
+/// The NES rotation system: shapes turn about a fixed centre cell and
+/// never kick.
+pub struct Nes;
+
+impl RotationSystem for Nes {
+    fn cells(&self, s: Shape, o: Orientation) -> [(usize, usize); 4] {
+        match s {
+            Shape::Tee => match o {
+                Orientation::Up => [(0, 1), (1, 1), (2, 1), (1, 0)],
+                Orientation::Right => [(1, 2), (0, 1), (1, 1), (1, 0)],
+                Orientation::Down => [(1, 2), (0, 1), (1, 1), (2, 1)],
+                Orientation::Left => [(1, 2), (1, 1), (2, 1), (1, 0)],
+            },
+            Shape::El => match o {
+                Orientation::Up => [(0, 1), (1, 1), (2, 1), (0, 0)],
+                Orientation::Right => [(0, 2), (1, 2), (1, 1), (1, 0)],
+                Orientation::Down => [(2, 2), (0, 1), (1, 1), (2, 1)],
+                Orientation::Left => [(1, 2), (1, 1), (1, 0), (2, 0)],
+            },
+            Shape::ElInv => match o {
+                Orientation::Up => [(0, 1), (1, 1), (2, 1), (2, 0)],
+                Orientation::Right => [(1, 2), (1, 1), (0, 0), (1, 0)],
+                Orientation::Down => [(0, 2), (0, 1), (1, 1), (2, 1)],
+                Orientation::Left => [(1, 2), (2, 2), (1, 1), (1, 0)],
+            },
+            Shape::ZeeInv => match o {
+                Orientation::Up | Orientation::Down => [(1, 1), (2, 1), (0, 0), (1, 0)],
+                Orientation::Right | Orientation::Left => [(1, 2), (1, 1), (2, 1), (2, 0)],
+            },
+            Shape::Zee => match o {
+                Orientation::Up | Orientation::Down => [(0, 1), (1, 1), (1, 0), (2, 0)],
+                Orientation::Right | Orientation::Left => [(2, 2), (1, 1), (2, 1), (1, 0)],
+            },
+            Shape::Eye => match o {
+                Orientation::Up | Orientation::Down => [(0, 1), (1, 1), (2, 1), (3, 1)],
+                Orientation::Right | Orientation::Left => [(2, 3), (2, 2), (2, 1), (2, 0)],
+            },
+            Shape::Square => [(0, 1), (1, 1), (0, 0), (1, 0)],
+        }
+    }
+
+    // shapes spawn around column 5
+    fn spawn_box(&self, s: Shape, width: usize, visible: usize) -> (usize, usize) {
+        (nes_column(s, width), visible + 1 - self.offset(s, Orientation::Up).1)
+    }
+}
+
+/// The original tetrix rotation: shapes sit in the bottom left of their
+/// matrix and kick left, then up, until they fit.
+pub struct Legacy;
+
+impl RotationSystem for Legacy {
+    fn cells(&self, s: Shape, o: Orientation) -> [(usize, usize); 4] {
+        let m = legacy_mat(s, o);
+        let mut cells = [(0, 0); 4];
+        let mut i = 0;
+        for y in 0..4 {
+            for x in 0..4 {
+                if m[3 - y][x] != None {
+                    cells[i] = (x, y);
+                    i += 1;
+                }
+            }
+        }
+        cells
+    }
+
+    fn spawn_box(&self, s: Shape, width: usize, visible: usize) -> (usize, usize) {
+        (nes_column(s, width), visible + 1)
+    }
+
+    fn mat(&self, s: Shape, o: Orientation) -> ShapeMat {
+        legacy_mat(s, o)
+    }
+
+    fn rotate(&self, s: Shape, _from: Orientation, to: Orientation, p: &Point, b: &Board) -> Option<Point> {
+        let m = self.rows(s, to);
+        let any_collide = |p: &Point| p.y == 0 || b.collides(&m, p);
+        let mut p = *p;
+        loop {
+            // kicked clean out of the top of the board, so give up
+            if p.y >= b.height() {
+                return None;
+            }
+            let mut xdelta = 0;
+            if !any_collide(&p) { return Some(p) }
+            if p.x != 0 {
+                p.x -= 1;
+                xdelta += 1;
+            }
+
+            if !any_collide(&p) { return Some(p) }
+            if s == Shape::Eye && p.x != 0 {
+                p.x -= 1;
+                xdelta += 1;
+            }
+
+            if !any_collide(&p) { return Some(p) }
+            if s == Shape::Eye && p.x != 0 {
+                p.x -= 1;
+                xdelta += 1;
+            }
+
+            if !any_collide(&p) { return Some(p) }
+            p.x += xdelta;
+            p.y += 1;
+        }
+    }
+}
+
+fn legacy_mat(s: Shape, o: Orientation) -> ShapeMat {
+    match s {
+        Shape::Tee => match o {
+            Orientation::Up => [
+                [None, None, None, None],
+                [None, None, None, None],
+                [None, Some(Shape::Tee), None, None],
+                [Some(Shape::Tee), Some(Shape::Tee), Some(Shape::Tee), None],
+            ],
+            Orientation::Down => [
+                [None, None, None, None],
+                [None, None, None, None],
+                [Some(Shape::Tee), Some(Shape::Tee), Some(Shape::Tee), None],
+                [None, Some(Shape::Tee), None, None],
+            ],
+            Orientation::Left => [
+                [None, None, None, None],
+                [None, Some(Shape::Tee), None, None],
+                [Some(Shape::Tee), Some(Shape::Tee), None, None],
+                [None, Some(Shape::Tee), None, None],
+            ],
+            Orientation::Right => [
+                [None, None, None, None],
+                [Some(Shape::Tee), None, None, None],
+                [Some(Shape::Tee), Some(Shape::Tee), None, None],
+                [Some(Shape::Tee), None, None, None],
+            ],
+        },
+        Shape::Eye => match o {
+            Orientation::Left | Orientation::Right => [
+                [None, None, None, None],
+                [None, None, None, None],
+                [None, None, None, None],
+                [Some(Shape::Eye),  Some(Shape::Eye),  Some(Shape::Eye),  Some(Shape::Eye)],
+            ],
+            Orientation::Up | Orientation::Down => [
+                [Some(Shape::Eye), None, None, None],
+                [Some(Shape::Eye), None, None, None],
+                [Some(Shape::Eye), None, None, None],
+                [Some(Shape::Eye), None, None, None],
+            ]
+        },
+        Shape::El => match o {
+            Orientation::Up => [
+                [None, None, None, None],
+                [Some(Shape::El),  None, None, None],
+                [Some(Shape::El),  None, None, None],
+                [Some(Shape::El),  Some(Shape::El),  None, None],
+            ],
+            Orientation::Left => [
+                [None, None, None, None],
+                [None, None, None, None],
+                [None, None, Some(Shape::El), None],
+                [Some(Shape::El),  Some(Shape::El),  Some(Shape::El), None],
+            ],
+            Orientation::Down => [
+                [None,  None,  None, None],
+                [Some(Shape::El), Some(Shape::El), None, None],
+                [None, Some(Shape::El), None, None],
+                [None, Some(Shape::El), None, None],
+            ],
+            Orientation::Right => [
+                [None, None, None, None],
+                [None, None, None, None],
+                [Some(Shape::El), Some(Shape::El), Some(Shape::El), None],
+                [Some(Shape::El),  None,  None, None],
+            ],
+        },
+        Shape::ElInv => match o {
+            Orientation::Up => [
+                [None, None, None, None],
+                [None,  Some(Shape::ElInv), None, None],
+                [None,  Some(Shape::ElInv), None, None],
+                [Some(Shape::ElInv),  Some(Shape::ElInv),  None, None],
+            ],
+            Orientation::Left => [
+                [None, None, None, None],
+                [None, None, None, None],
+                [Some(Shape::ElInv), Some(Shape::ElInv), Some(Shape::ElInv), None],
+                [None,  None,  Some(Shape::ElInv), None],
+            ],
+            Orientation::Down => [
+                [None, None, None, None],
+                [Some(Shape::ElInv), Some(Shape::ElInv), None, None],
+                [Some(Shape::ElInv), None, None, None],
+                [Some(Shape::ElInv),  None,  None, None],
+            ],
+            Orientation::Right => [
+                [None, None, None, None],
+                [None, None, None, None],
+                [Some(Shape::ElInv), None, None, None],
+                [Some(Shape::ElInv),  Some(Shape::ElInv),  Some(Shape::ElInv), None],
+            ],
+        },
+        Shape::Square => match o {
+            Orientation::Up | Orientation::Down | Orientation::Left | Orientation::Right => [
+                [None, None, None, None],
+                [None,  None, None, None],
+                [Some(Shape::Square),  Some(Shape::Square), None, None],
+                [Some(Shape::Square),  Some(Shape::Square),  None, None],
+            ]
+        },
+        Shape::ZeeInv => match o {
+            Orientation::Up | Orientation::Down => [
+                [None, None, None, None],
+                [Some(Shape::ZeeInv),  None, None, None],
+                [Some(Shape::ZeeInv),  Some(Shape::ZeeInv), None, None],
+                [None,  Some(Shape::ZeeInv),  None, None],
+            ],
+            Orientation::Left | Orientation::Right => [
+                [None, None, None, None],
+                [None, None, None, None],
+                [None, Some(Shape::ZeeInv), Some(Shape::ZeeInv), None],
+                [Some(Shape::ZeeInv),  Some(Shape::ZeeInv),  None, None],
+            ]
+        }
+        Shape::Zee => match o {
+            Orientation::Up | Orientation::Down => [
+                [None, None, None, None],
+                [None,  Some(Shape::Zee), None, None],
+                [Some(Shape::Zee),  Some(Shape::Zee), None, None],
+                [Some(Shape::Zee),  None,  None, None],
+            ],
+            Orientation::Left | Orientation::Right => [
+                [None, None, None, None],
+                [None, None, None, None],
+                [Some(Shape::Zee), Some(Shape::Zee), None, None],
+                [None,  Some(Shape::Zee),  Some(Shape::Zee), None],
+            ]
+        }
+    }
+}
+
+// the NES spawns shapes a column right of centre, all but the long bar
+fn nes_column(s: Shape, width: usize) -> usize {
+    match s {
+        Shape::Eye => width.saturating_sub(4) / 2,
+        _ => width.saturating_sub(3) / 2 + 1,
+    }
+}
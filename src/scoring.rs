@@ -0,0 +1,125 @@
+use crate::spin::Spin;
+
+/// Something the player did that may be worth points.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ScoreReason {
+    SoftDrop(u32), // cells fallen
+    HardDrop(u32), // cells fallen
+    Lines(u8), // lines cleared at once
+    TSpin(Spin, u8), // a T-spin and the lines it cleared, maybe none
+    Combo(u32), // clears in a row after the first
+    BackToBack(u32), // points for a difficult clear that followed another
+    PerfectClear(u8), // lines in the clear that emptied the board
+}
+
+/// Turns what the player did into points.
+pub trait ScoringSystem: Send {
+    fn points(&self, reason: &ScoreReason, level: u8) -> u32;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ScoringKind {
+    Guideline, Nes, Simple
+}
+
+impl ScoringKind {
+    pub fn build(&self) -> Box<dyn ScoringSystem> {
+        match self {
+            ScoringKind::Guideline => Box::new(Guideline),
+            ScoringKind::Nes => Box::new(Nes),
+            ScoringKind::Simple => Box::new(Simple),
+        }
+    }
+}
+
+/// 100/300/500/800 for one to four lines and 400/800/1200/1600 for a
+/// T-spin clearing none to three (a quarter to a half that for a mini),
+/// times the level.  Combos pay 50 a step times the level, and back to
+/// back difficult clears earn half as much again.  Emptying the board adds
+/// 800/1200/1800/2000 times the level.  A point for
+/// every soft dropped cell and two for every hard dropped one.
+pub struct Guideline;
+
+impl ScoringSystem for Guideline {
+    fn points(&self, reason: &ScoreReason, level: u8) -> u32 {
+        match *reason {
+            ScoreReason::SoftDrop(cells) => cells,
+            ScoreReason::HardDrop(cells) => 2 * cells,
+            ScoreReason::Lines(lines) => {
+                let base = match lines {
+                    0 => 0,
+                    1 => 100,
+                    2 => 300,
+                    3 => 500,
+                    _ => 800
+                };
+                return base * (level as u32).max(1);
+            },
+            ScoreReason::TSpin(spin, lines) => {
+                let base = match (spin, lines) {
+                    (Spin::Mini, 0) => 100,
+                    (Spin::Mini, 1) => 200,
+                    (Spin::Mini, _) => 400,
+                    (Spin::Full, 0) => 400,
+                    (Spin::Full, 1) => 800,
+                    (Spin::Full, 2) => 1200,
+                    (Spin::Full, _) => 1600
+                };
+                return base * (level as u32).max(1);
+            },
+            ScoreReason::Combo(n) => 50 * n * (level as u32).max(1),
+            ScoreReason::BackToBack(points) => points / 2,
+            ScoreReason::PerfectClear(lines) => {
+                let base = match lines {
+                    0 => 0,
+                    1 => 800,
+                    2 => 1200,
+                    3 => 1800,
+                    _ => 2000
+                };
+                return base * (level as u32).max(1);
+            }
+        }
+    }
+}
+
+/// 40/100/300/1200 times one more than the level.  The NES had no hard
+/// drop, but holding down paid a point a cell.
+pub struct Nes;
+
+impl ScoringSystem for Nes {
+    fn points(&self, reason: &ScoreReason, level: u8) -> u32 {
+        match *reason {
+            ScoreReason::SoftDrop(cells) => cells,
+            ScoreReason::HardDrop(_) => 0,
+            ScoreReason::Lines(lines) => {
+                let base = match lines {
+                    0 => 0,
+                    1 => 40,
+                    2 => 100,
+                    3 => 300,
+                    _ => 1200
+                };
+                return base * (level as u32 + 1);
+            },
+            // no T-spins on the NES, just the lines
+            ScoreReason::TSpin(_, lines) => self.points(&ScoreReason::Lines(lines), level),
+            ScoreReason::Combo(_) | ScoreReason::BackToBack(_) | ScoreReason::PerfectClear(_) => 0
+        }
+    }
+}
+
+/// A point a line, whatever the level, plus the usual drop points.
+pub struct Simple;
+
+impl ScoringSystem for Simple {
+    fn points(&self, reason: &ScoreReason, _level: u8) -> u32 {
+        match *reason {
+            ScoreReason::SoftDrop(cells) => cells,
+            ScoreReason::HardDrop(cells) => 2 * cells,
+            ScoreReason::Lines(lines) => lines as u32,
+            ScoreReason::TSpin(_, lines) => lines as u32,
+            ScoreReason::Combo(_) | ScoreReason::BackToBack(_) | ScoreReason::PerfectClear(_) => 0
+        }
+    }
+}
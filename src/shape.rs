@@ -23,19 +23,46 @@ impl PartialEq for Point {
 
 pub type ShapeMat = [[Option<Shape>; 4]; 4];
 
+/// A shape as row bitmasks, bottom row first, bit x set for column x.
+pub type ShapeRows = [u64; 4];
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Orientation {
     Up, Down, Left, Right,
 }
 
+impl Orientation {
+    pub fn cw(&self) -> Orientation {
+        match self {
+            Orientation::Up => Orientation::Right,
+            Orientation::Right => Orientation::Down,
+            Orientation::Down => Orientation::Left,
+            Orientation::Left => Orientation::Up
+        }
+    }
+
+    pub fn ccw(&self) -> Orientation {
+        match self {
+            Orientation::Up => Orientation::Left,
+            Orientation::Left => Orientation::Down,
+            Orientation::Down => Orientation::Right,
+            Orientation::Right => Orientation::Up
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Shape {
     Eye, El, ElInv, Square, Zee, ZeeInv, Tee
 }
 
 impl Shape {
-    pub fn random() -> Shape {
-        match rand::thread_rng().gen_range(0, 7) {
+    pub const ALL: [Shape; 7] = [
+        Shape::Eye, Shape::El, Shape::ElInv, Shape::Square, Shape::Zee, Shape::ZeeInv, Shape::Tee
+    ];
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Shape {
+        match rng.gen_range(0, 7) {
             0 => Shape::Eye,
             1 => Shape::El,
             2 => Shape::ElInv,
@@ -46,157 +73,4 @@ impl Shape {
             _ => panic!("wtf value is out of range")
         }	
     }
-
-    pub fn width(&self, o: &Orientation) -> usize {
-        match self {
-            Shape::Eye => match o {
-                Orientation::Left | Orientation::Right => 4,
-                Orientation::Up | Orientation::Down => 1,
-            },
-            Shape::Square => 2,
-            Shape::ElInv | Shape::El | Shape::ZeeInv | Shape::Zee => match o {
-                Orientation::Left | Orientation::Right => 3,
-                Orientation::Up | Orientation:: Down => 2,
-            },
-            Shape::Tee => {
-                match o {
-                    Orientation::Up | Orientation::Down => 3,
-                    Orientation::Left | Orientation::Right => 2, 
-                }
-            }
-        }
-    }
-
-    pub fn to_mat(&self, o: Orientation) -> ShapeMat {
-        match self {
-            Shape::Tee => match o {
-                Orientation::Up => [
-                    [None, None, None, None],
-                    [None, None, None, None],
-                    [None, Some(Shape::Tee), None, None],
-                    [Some(Shape::Tee), Some(Shape::Tee), Some(Shape::Tee), None],
-                ],
-                Orientation::Down => [
-                    [None, None, None, None],
-                    [None, None, None, None],
-                    [Some(Shape::Tee), Some(Shape::Tee), Some(Shape::Tee), None],
-                    [None, Some(Shape::Tee), None, None],
-                ],
-                Orientation::Left => [
-                    [None, None, None, None],
-                    [None, Some(Shape::Tee), None, None],
-                    [Some(Shape::Tee), Some(Shape::Tee), None, None],
-                    [None, Some(Shape::Tee), None, None],
-                ],
-                Orientation::Right => [
-                    [None, None, None, None],
-                    [Some(Shape::Tee), None, None, None],
-                    [Some(Shape::Tee), Some(Shape::Tee), None, None],
-                    [Some(Shape::Tee), None, None, None],
-                ],
-            },
-            Shape::Eye => match o {
-                Orientation::Left | Orientation::Right => [
-                    [None, None, None, None],
-                    [None, None, None, None],
-                    [None, None, None, None],
-                    [Some(Shape::Eye),  Some(Shape::Eye),  Some(Shape::Eye),  Some(Shape::Eye)],
-                ],
-                Orientation::Up | Orientation::Down => [
-                    [Some(Shape::Eye), None, None, None],
-                    [Some(Shape::Eye), None, None, None],
-                    [Some(Shape::Eye), None, None, None],
-                    [Some(Shape::Eye), None, None, None],
-                ]
-            },
-            Shape::El => match o {
-                Orientation::Up => [
-                    [None, None, None, None],
-                    [Some(Shape::El),  None, None, None],
-                    [Some(Shape::El),  None, None, None],
-                    [Some(Shape::El),  Some(Shape::El),  None, None],
-                ],
-                Orientation::Left => [
-                    [None, None, None, None],
-                    [None, None, None, None],
-                    [None, None, Some(Shape::El), None],
-                    [Some(Shape::El),  Some(Shape::El),  Some(Shape::El), None],
-                ],
-                Orientation::Down => [
-                    [None,  None,  None, None],
-                    [Some(Shape::El), Some(Shape::El), None, None],
-                    [None, Some(Shape::El), None, None],
-                    [None, Some(Shape::El), None, None],
-                ],
-                Orientation::Right => [
-                    [None, None, None, None],
-                    [None, None, None, None],
-                    [Some(Shape::El), Some(Shape::El), Some(Shape::El), None],
-                    [Some(Shape::El),  None,  None, None],
-                ],
-            },
-            Shape::ElInv => match o {
-                Orientation::Up => [
-                    [None, None, None, None],
-                    [None,  Some(Shape::ElInv), None, None],
-                    [None,  Some(Shape::ElInv), None, None],
-                    [Some(Shape::ElInv),  Some(Shape::ElInv),  None, None],
-                ],
-                Orientation::Left => [
-                    [None, None, None, None],
-                    [None, None, None, None],
-                    [Some(Shape::ElInv), Some(Shape::ElInv), Some(Shape::ElInv), None],
-                    [None,  None,  Some(Shape::ElInv), None],
-                ],
-                Orientation::Down => [
-                    [None, None, None, None],
-                    [Some(Shape::ElInv), Some(Shape::ElInv), None, None],
-                    [Some(Shape::ElInv), None, None, None],
-                    [Some(Shape::ElInv),  None,  None, None],
-                ],
-                Orientation::Right => [
-                    [None, None, None, None],
-                    [None, None, None, None],
-                    [Some(Shape::ElInv), None, None, None],
-                    [Some(Shape::ElInv),  Some(Shape::ElInv),  Some(Shape::ElInv), None],
-                ],
-            },
-            Shape::Square => match o {
-                Orientation::Up | Orientation::Down | Orientation::Left | Orientation::Right => [
-                    [None, None, None, None],
-                    [None,  None, None, None],
-                    [Some(Shape::Square),  Some(Shape::Square), None, None],
-                    [Some(Shape::Square),  Some(Shape::Square),  None, None],
-                ]
-            },
-            Shape::ZeeInv => match o {
-                Orientation::Up | Orientation::Down => [
-                    [None, None, None, None],
-                    [Some(Shape::ZeeInv),  None, None, None],
-                    [Some(Shape::ZeeInv),  Some(Shape::ZeeInv), None, None],
-                    [None,  Some(Shape::ZeeInv),  None, None],
-                ],
-                Orientation::Left | Orientation::Right => [
-                    [None, None, None, None],
-                    [None, None, None, None],
-                    [None, Some(Shape::ZeeInv), Some(Shape::ZeeInv), None],
-                    [Some(Shape::ZeeInv),  Some(Shape::ZeeInv),  None, None],
-                ]
-            }
-            Shape::Zee => match o {
-                Orientation::Up | Orientation::Down => [
-                    [None, None, None, None],
-                    [None,  Some(Shape::Zee), None, None],
-                    [Some(Shape::Zee),  Some(Shape::Zee), None, None],
-                    [Some(Shape::Zee),  None,  None, None],
-                ],
-                Orientation::Left | Orientation::Right => [
-                    [None, None, None, None],
-                    [None, None, None, None],
-                    [Some(Shape::Zee), Some(Shape::Zee), None, None],
-                    [None,  Some(Shape::Zee),  Some(Shape::Zee), None],
-                ]
-            }
-        }
-    }
-} 
\ No newline at end of file
+}
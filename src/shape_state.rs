@@ -1,37 +1,32 @@
-use crate::shape::{Shape, Orientation, Point};
+use crate::shape::{Shape, ShapeMat, ShapeRows, Orientation, Point};
 use crate::board::Board;
-use crate::WIDTH;
+use crate::rotation::RotationSystem;
+use std::sync::Arc;
 
 
 pub enum Direction {
-    Ccw, Cw
+    Ccw, Cw, Half
 }
 
 pub struct ShapeState {
     orientation: Orientation,
     position: Point,
-    shape: Shape
+    shape: Shape,
+    system: Arc<dyn RotationSystem>,
+    last_kick: Option<(isize, isize)> // the kick used, if the last move was a turn
 }
 
 impl ShapeState {
-    pub fn new_from_shape(s: Shape) -> ShapeState {
-        let position = match s {
-            Shape::Eye => Point{x: 3, y: 21},
-            _ => Point{x: 4, y:21},
-        };
-
+    pub fn new_from_shape(s: Shape, system: Arc<dyn RotationSystem>, width: usize, visible: usize) -> ShapeState {
         ShapeState {
             orientation: Orientation::Up,
-            position: position,
-            shape: s        
+            position: system.spawn(s, width, visible),
+            shape: s,
+            system: system,
+            last_kick: None
         }
     }
 
-    pub fn new() -> ShapeState {
-        let s = Shape::random();
-        ShapeState::new_from_shape(s)
-    }
-
     pub fn set_shape(&mut self, s: Shape) {
         self.shape = s;
     }
@@ -56,10 +51,34 @@ impl ShapeState {
         return self.orientation;
     }
 
+    /// How far the last turn was kicked away from a plain rotation, or
+    /// None when the shape has moved since it last turned.
+    pub fn last_kick(&self) -> Option<(isize, isize)> {
+        return self.last_kick;
+    }
+
+    pub fn mat(&self) -> ShapeMat {
+        return self.system.mat(self.shape, self.orientation);
+    }
+
+    pub fn rows(&self) -> ShapeRows {
+        return self.system.rows(self.shape, self.orientation);
+    }
+
+    /// Puts the shape's cells on the board where it is now.
+    pub fn occupy(&self, b: &mut Board) {
+        b.occupy(&self.rows(), &self.position, self.shape.into());
+    }
+
+    pub fn vacate(&self, b: &mut Board) {
+        b.vacate(&self.rows(), &self.position);
+    }
+
     pub fn down(&mut self) -> bool {
         let startpos = self.position.y;
         if self.position.y > 0 {
             self.position.y -= 1;
+            self.last_kick = None;
         }
         return startpos != self.position.y;
     }
@@ -72,107 +91,67 @@ impl ShapeState {
         if self.shape_collide(b) {
             self.position.x += 1;
         }
+        if startpos != self.position.x {
+            self.last_kick = None;
+        }
         return startpos != self.position.x
     }
 
     pub fn right(&mut self, b: &Board) -> bool {
         let startpos = self.position.x;
-        if self.position.x <= WIDTH {
+        if self.position.x <= b.width() {
             self.position.x += 1;
         }
         if self.shape_collide(b) {
             self.position.x -= 1;
         }
-        return startpos != self.position.x;
-    }
-
-    pub fn rotate(&mut self, d: Direction, b: &Board) {
-        match d {
-            Direction::Ccw => self.rotate_ccw(),
-            Direction::Cw => self.rotate_cw()
-        }
-
-        
-        loop {
-            let mut xdelta = 0;          
-            if !self.any_collide(b) { return }
-            if self.position.x != 0 {
-                self.position.x -= 1;
-                xdelta += 1;
-            }
-
-            if !self.any_collide(b) { return }
-            if self.shape == Shape::Eye && self.position.x != 0 {                
-                self.position.x -= 1;
-                xdelta += 1;
-            }
-
-            if !self.any_collide(b) { return }
-            if self.shape == Shape::Eye && self.position.x != 0 {                
-                self.position.x -= 1;
-                xdelta += 1;
-            }
-            
-            if !self.any_collide(b) { return }
-            self.position.x += xdelta;
-            self.position.y += 1;        
-        }
-
-    }
-
-    pub fn rotate_cw(&mut self) {
-        self.orientation = match self.orientation {
-            Orientation::Up => Orientation::Right,
-            Orientation::Right => Orientation::Down,
-            Orientation::Down => Orientation::Left,
-            Orientation::Left => Orientation::Up
+        if startpos != self.position.x {
+            self.last_kick = None;
         }
+        return startpos != self.position.x;
     }
 
-    pub fn rotate_ccw(&mut self) {
-        self.orientation = match self.orientation {
-            Orientation::Up => Orientation::Left,
-            Orientation::Left => Orientation::Down,
-            Orientation::Down => Orientation::Right,
-            Orientation::Right => Orientation::Up
+    pub fn rotate(&mut self, d: Direction, b: &Board) -> bool {
+        let to = match d {
+            Direction::Ccw => self.orientation.ccw(),
+            Direction::Cw => self.orientation.cw(),
+            Direction::Half => self.orientation.cw().cw()
+        };
+        match self.system.rotate(self.shape, self.orientation, to, &self.position, b) {
+            Some(p) => {
+                let (from_x, from_y) = self.system.offset(self.shape, self.orientation);
+                let (to_x, to_y) = self.system.offset(self.shape, to);
+                self.last_kick = Some((
+                    p.x as isize - to_x as isize - (self.position.x as isize - from_x as isize),
+                    p.y as isize - to_y as isize - (self.position.y as isize - from_y as isize)
+                ));
+                self.position = p;
+                self.orientation = to;
+                return true;
+            },
+            None => return false
         }
     }
 
     pub fn shape_collide(&self, b: &Board) -> bool {
-        let width = self.shape.width(&self.orientation); // 4; 4
-        let position = &self.position; // x = 7, y = 3; x = 6, y = 3; 5, 3
-        if position.x + width > WIDTH { return true } // true; 
-        let mat = &self.shape.to_mat(self.orientation);
-        for my in 0..=3 {
-            for mx in 0..=3 {
-                if position.x + mx >= WIDTH && mat[3 - my][mx] != None {
-                    return true
-                }
-                if mat[3 - my][mx] != None && b.0[position.y + my][position.x + mx] != None {
-                    return true
-                }
-            }
-        }
-        return false;
+        return b.collides(&self.rows(), &self.position);
     }
 
-    pub fn any_collide(&self, b: &Board) -> bool {
-        if self.position.y == 0 {return true}
-        return self.shape_collide(b);
+    /// Where the shape would land if it were dropped now.
+    pub fn ghost(&self, b: &Board) -> Point {
+        let m = self.rows();
+        let mut p = self.position;
+        while p.y > 0 && !b.collides(&m, &Point::new(p.x, p.y - 1)) {
+            p.y -= 1;
+        }
+        return p;
     }
 
     pub fn drop(&mut self, b: &Board) {
-        loop {
-            if self.position.y != 0 {
-                self.position.y -= 1;
-            }            
-            if self.shape_collide(b) {
-                self.position.y += 1;
-                break;
-            }
-            if self.position.y == 0 {
-                break;
-            }
+        let ghost = self.ghost(b);
+        if ghost != self.position {
+            self.position = ghost;
+            self.last_kick = None;
         }
     }
 }
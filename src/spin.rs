@@ -0,0 +1,52 @@
+use crate::board::Board;
+use crate::shape::Shape;
+use crate::shape_state::ShapeState;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Spin {
+    Mini, Full
+}
+
+/// The three corner rule: a T that got where it is by turning, with at
+/// least three of the four squares diagonal to its centre filled (walls and
+/// floor count), is a T-spin.  It is a full spin when both corners beside
+/// the point are filled, or when the turn took the far kick that reaches
+/// into a T-spin triple slot, and a mini otherwise.
+pub(crate) fn t_spin(s: &ShapeState, b: &Board) -> Option<Spin> {
+    if s.shape() != Shape::Tee {
+        return None;
+    }
+    let (kx, ky) = s.last_kick()?;
+
+    let rows = s.rows();
+    let p = s.position();
+    let mut cells = Vec::new();
+    for y in 0..4 {
+        for x in 0..4 {
+            if rows[y] & 1 << x != 0 {
+                cells.push(((p.x + x) as isize, (p.y + y) as isize));
+            }
+        }
+    }
+    // the centre touches all three other cells
+    let (cx, cy) = *cells.iter().find(|(x, y)| {
+        cells.iter().filter(|(ox, oy)| (ox - x).abs() + (oy - y).abs() == 1).count() == 3
+    })?;
+    // the point is the arm with nothing opposite it
+    let (dx, dy) = cells.iter()
+        .map(|(x, y)| (x - cx, y - cy))
+        .find(|(dx, dy)| (*dx, *dy) != (0, 0) && !cells.contains(&(cx - dx, cy - dy)))?;
+
+    let front = [(cx + dx - dy, cy + dy - dx), (cx + dx + dy, cy + dy + dx)];
+    let back = [(cx - dx - dy, cy - dy - dx), (cx - dx + dy, cy - dy + dx)];
+    let front_filled = front.iter().filter(|(x, y)| b.blocked(*x, *y)).count();
+    let back_filled = back.iter().filter(|(x, y)| b.blocked(*x, *y)).count();
+
+    if front_filled + back_filled < 3 {
+        return None;
+    }
+    if front_filled == 2 || (kx.abs() == 1 && ky.abs() == 2) {
+        return Some(Spin::Full);
+    }
+    return Some(Spin::Mini);
+}
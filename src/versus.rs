@@ -0,0 +1,175 @@
+use crate::event::Output;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use uuid::Uuid;
+
+/// Who a player's attacks go to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Targeting {
+    Random, // any opponent still playing
+    Attackers, // everyone targeting this player, or a random opponent if nobody is
+    KOs, // the opponent nearest to topping out
+    Even, // the opponent sent the least garbage so far
+}
+
+/// How a match finished.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchResult {
+    pub winner: Option<Uuid>, // None when the last players went out
+    pub standings: Vec<Uuid>, // winner first, then the rest in reverse order of going out
+    pub kos: Vec<(Uuid, u32)>, // opponents each player finished off, in the order they joined
+}
+
+struct Player {
+    id: Uuid,
+    targeting: Targeting,
+    target: Option<Uuid>, // who this player's last attack went to
+    attacker: Option<Uuid>, // who last sent this player garbage, credited with the KO
+    kos: u32,
+    received: u32, // garbage sent this player so far
+    stack: usize, // rows high the stack stood when last seen
+    pending: u32,
+}
+
+/// A match between games that send each other garbage.  Feed it every
+/// output from every player and it says where garbage should go; the match
+/// is over when all but one player has gone out.
+pub struct Versus {
+    players: Vec<Player>,
+    out: Vec<Uuid>, // players who have gone out, first out first
+    rng: StdRng,
+}
+
+impl Versus {
+    pub fn new(players: &[Uuid], targeting: Targeting, seed: u64) -> Versus {
+        let players = players.iter().map(|id| Player {
+            id: *id,
+            targeting: targeting,
+            target: None,
+            attacker: None,
+            kos: 0,
+            received: 0,
+            stack: 0,
+            pending: 0,
+        }).collect();
+        return Versus { players: players, out: Vec::new(), rng: StdRng::seed_from_u64(seed) };
+    }
+
+    pub fn players(&self) -> Vec<Uuid> {
+        return self.players.iter().map(|p| p.id).collect();
+    }
+
+    pub fn set_targeting(&mut self, id: Uuid, targeting: Targeting) -> bool {
+        match self.players.iter_mut().find(|p| p.id == id) {
+            Some(p) => {
+                p.targeting = targeting;
+                return true;
+            },
+            None => return false
+        }
+    }
+
+    fn alive(&self) -> Vec<Uuid> {
+        return self.players.iter().map(|p| p.id).filter(|id| !self.out.contains(id)).collect();
+    }
+
+    fn player(&mut self, id: Uuid) -> Option<&mut Player> {
+        return self.players.iter_mut().find(|p| p.id == id);
+    }
+
+    pub fn is_over(&self) -> bool {
+        return !self.out.is_empty() && self.alive().len() <= 1;
+    }
+
+    pub fn result(&self) -> Option<MatchResult> {
+        if !self.is_over() {
+            return None;
+        }
+        let winner = self.alive().first().cloned();
+        let mut standings: Vec<Uuid> = winner.iter().cloned().collect();
+        standings.extend(self.out.iter().rev());
+        return Some(MatchResult {
+            winner: winner,
+            standings: standings,
+            kos: self.players.iter().map(|p| (p.id, p.kos)).collect(),
+        });
+    }
+
+    // the opponents a player's attack goes to
+    fn targets(&mut self, from: Uuid) -> Vec<Uuid> {
+        let opponents: Vec<Uuid> = self.alive().into_iter().filter(|id| *id != from).collect();
+        if opponents.is_empty() {
+            return Vec::new();
+        }
+        let targeting = self.players.iter().find(|p| p.id == from).map_or(Targeting::Random, |p| p.targeting);
+        let by = |f: &dyn Fn(&Player) -> i64| {
+            // ties go to whoever joined first
+            self.players.iter()
+                .filter(|p| opponents.contains(&p.id))
+                .fold(None, |best: Option<&Player>, p| match best {
+                    Some(b) if f(b) >= f(p) => Some(b),
+                    _ => Some(p)
+                })
+                .map(|p| p.id)
+        };
+        let target = match targeting {
+            Targeting::Random => None,
+            Targeting::Attackers => {
+                let attackers: Vec<Uuid> = self.players.iter()
+                    .filter(|p| opponents.contains(&p.id) && p.target == Some(from))
+                    .map(|p| p.id)
+                    .collect();
+                if !attackers.is_empty() {
+                    return attackers;
+                }
+                None
+            },
+            Targeting::KOs => by(&|p| (p.stack + p.pending as usize) as i64),
+            Targeting::Even => by(&|p| -(p.received as i64)),
+        };
+        return vec![target.unwrap_or_else(|| *opponents.choose(&mut self.rng).unwrap())];
+    }
+
+    /// Takes note of an output from one of the players and returns the
+    /// garbage it sends, as rows for each opponent.
+    pub fn observe(&mut self, from: Uuid, o: &Output) -> Vec<(Uuid, u32)> {
+        if self.is_over() || self.out.contains(&from) || self.player(from).is_none() {
+            return Vec::new();
+        }
+        match o {
+            Output::GarbageSent(lines) => {
+                let targets = self.targets(from);
+                for to in targets.iter() {
+                    let p = self.player(*to).unwrap();
+                    p.received += lines;
+                    p.attacker = Some(from);
+                }
+                if let Some(to) = targets.last() {
+                    self.player(from).unwrap().target = Some(*to);
+                }
+                return targets.into_iter().map(|to| (to, *lines)).collect();
+            },
+            Output::PendingGarbage(lines) => {
+                self.player(from).unwrap().pending = *lines;
+            },
+            // BoardUpdate still has the falling shape on it, so the stack is
+            // only measured once something locks
+            Output::ShapeLocked(_, b) | Output::LineCompleted(_, b) => {
+                self.player(from).unwrap().stack = b.stack_height();
+            },
+            Output::GarbageReceived(lines) => {
+                self.player(from).unwrap().stack += *lines as usize;
+            },
+            Output::GameOver(_) => {
+                self.out.push(from);
+                let attacker = self.player(from).unwrap().attacker;
+                if let Some(attacker) = attacker.filter(|a| !self.out.contains(a)) {
+                    self.player(attacker).unwrap().kos += 1;
+                }
+            },
+            _ => {}
+        }
+        return Vec::new();
+    }
+}